@@ -1,19 +1,55 @@
 extern crate wikistory;
 use wikistory::xml_wiki_parser::generate_index as generate_index;
+use wikistory::xml_wiki_parser::generate_backlinks as generate_backlinks;
+use std::fs;
 use std::fs::File;
 use std::fs::OpenOptions;
+use std::path::Path;
+
+const XML_PATH: &'static str = "./data/enwiki-20170820-pages-articles.xml";
+const INDEX_PATH: &'static str = "./data/index.csv";
+const REDIRECTS_PATH: &'static str = "./data/redirects.tsv";
+const BACKLINKS_PATH: &'static str = "./data/backlinks.tsv";
+
+/// The tables are written to this file until they are complete, then renamed into place, so that
+/// an interrupted run never leaves a truncated table behind for `wikistory` to load.
+fn partial_path(path: &str) -> String {
+    format!("{}.partial", path)
+}
 
 fn main() {
-    // 1. Open XML data file to read from:
-    let xml_file = File::open("./data/enwiki-20170820-pages-articles.xml").expect("File not found.");
+    // The redirects table is only renamed into place once the index is complete; there is
+    // nothing left to index then.
+    if !Path::new(REDIRECTS_PATH).exists() {
+        // 1. Open XML data file to read from:
+        let xml_file = File::open(XML_PATH).expect("File not found.");
+
+        // 2: Open the index output file:
+        let index_file = OpenOptions::new()
+                            .read(true)
+                            .write(true)
+                            .create(true) // Or create a new file if it does not exist
+                            .open(INDEX_PATH)
+                            .expect("Unable to create index file.");
+        // 3: Open the partial redirects file; new redirects are appended when resuming:
+        let redirects_file = OpenOptions::new()
+                            .append(true)
+                            .create(true)
+                            .open(partial_path(REDIRECTS_PATH))
+                            .expect("Unable to create redirects file.");
+        // 4. Index the file:
+        generate_index(xml_file, index_file, &redirects_file).expect("Unable to index the XML file.");
+        redirects_file.sync_all().expect("Unable to write the redirects file.");
+        fs::rename(partial_path(REDIRECTS_PATH), REDIRECTS_PATH).expect("Unable to write the redirects file.");
+    }
 
-    // 2: Open the index output file:
-    let index_file = OpenOptions::new()
-                        .read(true)
-                        .write(true)
-                        .create(true) // Or create a new file if it does not exist
-                        .open("./data/index.csv")
-                        .expect("Unable to create index file.");
-    // 3. Index the file:
-    generate_index(xml_file, index_file);
+    // 5. Index the backlinks in a second pass; this one cannot be resumed, so it starts over
+    // unless it was already done:
+    if !Path::new(BACKLINKS_PATH).exists() {
+        let backlinks_file = File::create(partial_path(BACKLINKS_PATH)).expect("Unable to create backlinks file.");
+        let xml_file = File::open(XML_PATH).expect("File not found.");
+        generate_backlinks(xml_file, &backlinks_file).expect("Unable to index the backlinks of the XML file.");
+        backlinks_file.sync_all().expect("Unable to write the backlinks file.");
+        fs::rename(partial_path(BACKLINKS_PATH), BACKLINKS_PATH).expect("Unable to write the backlinks file.");
+    }
 }
@@ -1,8 +1,19 @@
+#[macro_use]
 extern crate clap;
 extern crate wikistory;
+use wikistory::story_builder::article_provider::ThreadedAP;
+use wikistory::story_builder::article_provider::caching_article_provider::CachingArticleProvider;
 use wikistory::story_builder::article_provider::http_article_provider::HTTPArticleProvider;
-use wikistory::story_builder::story_builder::StoryBuilder;
+use wikistory::story_builder::article_provider::mediawiki_api_provider::MediaWikiApiProvider;
+use wikistory::story_builder::article_provider::memory_caching_article_provider::MemoryCachingArticleProvider;
+use wikistory::story_builder::article_provider::replay_article_provider::{RecordingArticleProvider, ReplayArticleProvider};
+use wikistory::story_builder::article_provider::xml_dump_article_provider::XmlDumpArticleProvider;
+use wikistory::story_builder::exclusion::ExclusionRule;
+use wikistory::story_builder::story::{render_json, render_json_error, render_json_stories, render_plain_text};
+use wikistory::story_builder::story_builder::{StoryBuilder, StoryBuilderConfig};
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use clap::{App, Arg};
 
 /// The main entry point for WikiStory. It is tasked with reading user input to
@@ -13,21 +24,242 @@ fn main() {
         .about("Builds a story from one topic to another using links in wikipedia articles.")
         .arg(Arg::with_name("Starting topic").required(true))
         .arg(Arg::with_name("Final topic").required(true))
+        .arg(
+            Arg::with_name("dump")
+                .long("dump")
+                .takes_value(true)
+                .help("Read articles from this local XML dump instead of wikipedia.org"),
+        )
+        .arg(
+            Arg::with_name("index")
+                .long("index")
+                .takes_value(true)
+                .default_value("./data/index.csv")
+                .help("Index of the XML dump, as generated by wiki_xml_indexer"),
+        )
+        .arg(
+            Arg::with_name("redirects")
+                .long("redirects")
+                .takes_value(true)
+                .default_value("./data/redirects.tsv")
+                .help("Redirects table of the XML dump, as generated by wiki_xml_indexer"),
+        )
+        .arg(
+            Arg::with_name("backlinks")
+                .long("backlinks")
+                .takes_value(true)
+                .default_value("./data/backlinks.tsv")
+                .help("Backlinks table of the XML dump, as generated by wiki_xml_indexer"),
+        )
+        .arg(
+            Arg::with_name("lang")
+                .long("lang")
+                .takes_value(true)
+                .default_value("en")
+                .help("Language edition of wikipedia to read articles from (en, fr, de, ...)"),
+        )
+        .arg(
+            Arg::with_name("wiki-url")
+                .long("wiki-url")
+                .takes_value(true)
+                .help("Read articles from the MediaWiki site at this URL instead of wikipedia.org"),
+        )
+        .arg(
+            Arg::with_name("api")
+                .long("api")
+                .conflicts_with_all(&["dump", "record", "replay"])
+                .help("Read articles through the MediaWiki API instead of the HTML pages of wikipedia"),
+        )
+        .arg(
+            Arg::with_name("record")
+                .long("record")
+                .takes_value(true)
+                .conflicts_with_all(&["dump", "replay"])
+                .help("Record the pages fetched from wikipedia in this directory"),
+        )
+        .arg(
+            Arg::with_name("replay")
+                .long("replay")
+                .takes_value(true)
+                .conflicts_with("dump")
+                .help("Read articles from the pages previously recorded in this directory"),
+        )
+        .arg(
+            Arg::with_name("include-lists")
+                .long("include-lists")
+                .conflicts_with_all(&["dump", "api"])
+                .help("Also follow the links of the lists, tables and infoboxes of the articles, not only of their prose"),
+        )
+        .arg(
+            Arg::with_name("cache")
+                .long("cache")
+                .takes_value(true)
+                .help("Keep the articles fetched in this directory, to reuse them in the next runs"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["text", "json"])
+                .default_value("text")
+                .help("Print the story as human-readable text or as a JSON document"),
+        )
+        .arg(
+            Arg::with_name("stories")
+                .long("stories")
+                .takes_value(true)
+                .help("Build up to this many distinct stories, shortest first, to pick the best one from"),
+        )
+        .arg(
+            Arg::with_name("via")
+                .long("via")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .conflicts_with("stories")
+                .help("Make the story pass through this topic (may be given several times, in order)"),
+        )
+        .arg(
+            Arg::with_name("avoid")
+                .long("avoid")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help(
+                    "Never make the story pass through this topic, the topics starting with a prefix \
+                     (\"List of *\"), <years> or <countries> (may be given several times)",
+                ),
+        )
+        .arg(
+            Arg::with_name("max-depth")
+                .long("max-depth")
+                .takes_value(true)
+                .default_value("5")
+                .help("Maximum number of hops of the story"),
+        )
+        .arg(
+            Arg::with_name("max-articles")
+                .long("max-articles")
+                .takes_value(true)
+                .help("Give up after fetching this many articles"),
+        )
+        .arg(
+            Arg::with_name("timeout")
+                .long("timeout")
+                .takes_value(true)
+                .help("Give up after searching for this many seconds"),
+        )
+        .arg(
+            Arg::with_name("max-frontier")
+                .long("max-frontier")
+                .takes_value(true)
+                .help("Give up when a level of the search holds more than this many articles"),
+        )
         .get_matches();
 
     let first_topic = args.value_of("Starting topic").unwrap();
     let end_topic = args.value_of("Final topic").unwrap();
+    let waypoints: Vec<&str> = args.values_of("via").map(|values| values.collect()).unwrap_or_default();
+    // The JSON document must be the only thing printed, so that it can be parsed.
+    let json = args.value_of("format") == Some("json");
 
-    println!(
-        "Wikistory will now try to generate a story from <{}> to <{}>: ",
-        first_topic,
-        end_topic
-    );
+    if !json {
+        println!(
+            "Wikistory will now try to generate a story from <{}> to <{}>: ",
+            first_topic,
+            end_topic
+        );
+        if !waypoints.is_empty() {
+            println!("(passing through <{}>)", waypoints.join(">, <"));
+        }
+    }
 
-    let provider = HTTPArticleProvider::new();
-    let mut sb = StoryBuilder::new(Arc::new(provider));
-    match sb.build_story(&first_topic, &end_topic) {
-        Ok(text) => println!("{}", text),
-        Err(err) => println!("{}", err),
+    let provider: Box<ThreadedAP> = match args.value_of("dump") {
+        Some(dump) => {
+            let mut provider = XmlDumpArticleProvider::new(dump, args.value_of("index").unwrap())
+                .expect("Unable to load the XML dump index.");
+            // Indexes generated before redirects were handled have no redirects table.
+            let redirects = Path::new(args.value_of("redirects").unwrap());
+            if redirects.exists() {
+                provider
+                    .load_redirects(redirects)
+                    .expect("Unable to load the XML dump redirects.");
+            }
+            // Without backlinks, the search can only go forward from the start topic.
+            let backlinks = Path::new(args.value_of("backlinks").unwrap());
+            if backlinks.exists() {
+                provider
+                    .load_backlinks(backlinks)
+                    .expect("Unable to load the XML dump backlinks.");
+            }
+            Box::new(provider)
+        }
+        None if args.is_present("api") => match args.value_of("wiki-url") {
+            Some(wiki_url) => Box::new(MediaWikiApiProvider::with_base_url(wiki_url)),
+            None => Box::new(MediaWikiApiProvider::with_language(args.value_of("lang").unwrap())),
+        },
+        None => {
+            let include_lists = args.is_present("include-lists");
+            let http_provider = match args.value_of("wiki-url") {
+                Some(wiki_url) => HTTPArticleProvider::with_base_url(wiki_url),
+                None => HTTPArticleProvider::with_language(args.value_of("lang").unwrap()),
+            }.include_lists(include_lists);
+            match (args.value_of("record"), args.value_of("replay")) {
+                (Some(record), _) => Box::new(
+                    RecordingArticleProvider::new(http_provider, record)
+                        .expect("Unable to create the recording directory."),
+                ),
+                (None, Some(replay)) => Box::new(ReplayArticleProvider::new(replay).include_lists(include_lists)),
+                (None, None) => Box::new(http_provider),
+            }
+        }
     };
+    let provider: Box<ThreadedAP> = match args.value_of("cache") {
+        Some(cache) => Box::new(
+            CachingArticleProvider::new(provider, cache).expect("Unable to create the cache directory."),
+        ),
+        None => provider,
+    };
+    // Threads reaching the same topic at the same time share a single fetch.
+    let provider: Arc<ThreadedAP> = Arc::new(MemoryCachingArticleProvider::new(provider));
+    let mut config = StoryBuilderConfig::new().max_depth(value_t_or_exit!(args, "max-depth", u8));
+    if args.is_present("max-articles") {
+        config = config.max_articles(value_t_or_exit!(args, "max-articles", usize));
+    }
+    if args.is_present("timeout") {
+        config = config.timeout(Duration::from_secs(value_t_or_exit!(args, "timeout", u64)));
+    }
+    if args.is_present("max-frontier") {
+        config = config.max_frontier(value_t_or_exit!(args, "max-frontier", usize));
+    }
+    for rule in args.values_of("avoid").into_iter().flatten() {
+        config = config.avoid(ExclusionRule::parse(rule));
+    }
+    let mut sb = StoryBuilder::with_config(provider, config);
+    let count = if args.is_present("stories") {
+        value_t_or_exit!(args, "stories", usize)
+    } else {
+        1
+    };
+    let result = if waypoints.is_empty() {
+        sb.build_stories(&first_topic, &end_topic, count)
+    } else {
+        sb.build_story_via(&first_topic, &waypoints, &end_topic).map(|story| vec![story])
+    };
+    match (result, json) {
+        // A single story is printed on its own.
+        (Ok(ref stories), true) if count == 1 => println!("{}", render_json(&stories[0])),
+        (Ok(ref stories), false) if count == 1 => println!("{}", render_plain_text(&stories[0])),
+        (Ok(stories), true) => println!("{}", render_json_stories(&stories)),
+        (Ok(stories), false) => {
+            for (index, story) in stories.iter().enumerate() {
+                println!("Story {}:\r\n{}", index + 1, render_plain_text(story));
+            }
+        }
+        (Err(err), true) => println!("{}", render_json_error(&err)),
+        (Err(err), false) => println!("{}", err),
+    };
+    if !json {
+        println!("({} articles fetched)", sb.articles_fetched());
+    }
 }
@@ -0,0 +1,143 @@
+mod entities;
+
+use self::entities::NAMED_ENTITIES;
+
+/// Length of the longest name of a named character reference.
+const MAX_ENTITY_NAME_LEN: usize = 32;
+
+/// Replaces the character references of the HTML `text` (`&amp;`, `&nbsp;`, `&#160;`, `&#xA0;`, ...)
+/// with the characters they stand for, the way browsers do. Any other `&` is kept as is.
+pub fn decode_entities(text: &str) -> String {
+    decode(text, false)
+}
+
+/// Same as `decode_entities`, for the value of an attribute: a legacy reference (without its `;`)
+/// followed by a letter, a digit or `=` is part of the value, as in `href="?a=1&copy=2"`.
+pub fn decode_attribute(value: &str) -> String {
+    decode(value, true)
+}
+
+/// Replaces each run of whitespace of the `text` (including line breaks and non-breaking spaces)
+/// with a single space, and removes the whitespace at both ends.
+pub fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn decode(text: &str, in_attribute: bool) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp + 1..];
+        match decode_reference(rest, in_attribute) {
+            Some((replacement, len)) => {
+                decoded.push_str(&replacement);
+                rest = &rest[len..];
+            }
+            None => decoded.push('&'),
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Decode the character reference found at the start of the `text` (right after its `&`), and
+/// return the text it stands for along with the length of the reference.
+fn decode_reference(text: &str, in_attribute: bool) -> Option<(String, usize)> {
+    if text.starts_with('#') {
+        decode_numeric(&text[1..]).map(|(c, len)| (c.to_string(), len + 1))
+    } else {
+        decode_named(text, in_attribute).map(|(decoded, len)| (decoded.to_owned(), len))
+    }
+}
+
+fn decode_numeric(text: &str) -> Option<(char, usize)> {
+    let (radix, start) = if text.starts_with('x') || text.starts_with('X') {
+        (16, 1)
+    } else {
+        (10, 0)
+    };
+    let digits = text[start..].chars().take_while(|c| c.is_digit(radix)).count();
+    if digits == 0 {
+        return None;
+    }
+    let mut len = start + digits;
+    // Values too large for an u32 are out of the range of unicode anyway.
+    let code = u32::from_str_radix(&text[start..len], radix).unwrap_or(u32::max_value());
+    if text[len..].starts_with(';') {
+        len += 1;
+    }
+    Some((numeric_char(code), len))
+}
+
+/// The character of a numeric reference. Browsers read the C1 control codes as Windows-1252
+/// characters, and replace the invalid ones with U+FFFD.
+fn numeric_char(code: u32) -> char {
+    match code {
+        0 => '\u{FFFD}',
+        0x80 => '\u{20AC}',
+        0x82 => '\u{201A}',
+        0x83 => '\u{0192}',
+        0x84 => '\u{201E}',
+        0x85 => '\u{2026}',
+        0x86 => '\u{2020}',
+        0x87 => '\u{2021}',
+        0x88 => '\u{02C6}',
+        0x89 => '\u{2030}',
+        0x8A => '\u{0160}',
+        0x8B => '\u{2039}',
+        0x8C => '\u{0152}',
+        0x8E => '\u{017D}',
+        0x91 => '\u{2018}',
+        0x92 => '\u{2019}',
+        0x93 => '\u{201C}',
+        0x94 => '\u{201D}',
+        0x95 => '\u{2022}',
+        0x96 => '\u{2013}',
+        0x97 => '\u{2014}',
+        0x98 => '\u{02DC}',
+        0x99 => '\u{2122}',
+        0x9A => '\u{0161}',
+        0x9B => '\u{203A}',
+        0x9C => '\u{0153}',
+        0x9E => '\u{017E}',
+        0x9F => '\u{0178}',
+        _ => ::std::char::from_u32(code).unwrap_or('\u{FFFD}'),
+    }
+}
+
+fn decode_named(text: &str, in_attribute: bool) -> Option<(&'static str, usize)> {
+    let name_len = text.bytes()
+        .take(MAX_ENTITY_NAME_LEN)
+        .take_while(|b| b.is_ascii_alphanumeric())
+        .count();
+    if name_len == 0 {
+        return None;
+    }
+    if text[name_len..].starts_with(';') {
+        if let Some(decoded) = lookup(&text[..name_len + 1]) {
+            return Some((decoded, name_len + 1));
+        }
+    }
+    // Legacy references may omit their `;`; the longest one wins ("&notit;" is "¬it;").
+    for len in (1..name_len + 1).rev() {
+        if let Some(decoded) = lookup(&text[..len]) {
+            let next = text[len..].chars().next();
+            if in_attribute && next.map_or(false, |c| c.is_ascii_alphanumeric() || c == '=') {
+                return None;
+            }
+            return Some((decoded, len));
+        }
+    }
+    None
+}
+
+fn lookup(name: &str) -> Option<&'static str> {
+    NAMED_ENTITIES
+        .binary_search_by(|&(entity, _)| entity.cmp(name))
+        .ok()
+        .map(|index| NAMED_ENTITIES[index].1)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,64 @@
+use super::*;
+
+#[test]
+fn decode_entities_without_references_returns_text() {
+    assert_eq!(decode_entities(""), "");
+    assert_eq!(decode_entities("Montreal is a city."), "Montreal is a city.");
+}
+
+#[test]
+fn decode_entities_decodes_numeric_references() {
+    assert_eq!(decode_entities("caf&#233; &#xE9;&#XE9;"), "café éé");
+    assert_eq!(decode_entities("1&#160;km"), "1\u{A0}km");
+    // The `;` is optional.
+    assert_eq!(decode_entities("&#65&#x42 C"), "AB C");
+}
+
+#[test]
+fn decode_entities_replaces_invalid_numeric_references() {
+    assert_eq!(decode_entities("&#0;&#xD800;&#x110000;&#99999999999;"), "\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}");
+    assert_eq!(decode_entities("&#x80;&#150;&#x9F;"), "€–Ÿ");
+}
+
+#[test]
+fn decode_entities_decodes_named_references() {
+    assert_eq!(
+        decode_entities("AT&amp;T &lt;b&gt; &quot;q&quot; &apos;a&apos; 5&nbsp;km"),
+        "AT&T <b> \"q\" 'a' 5\u{A0}km"
+    );
+    assert_eq!(decode_entities("&mdash;&hellip;&rarr;&Eacute;"), "—…→É");
+    // HTML5 references missing from HTML4.
+    assert_eq!(decode_entities("&NewLine;&bigstar;&nGt;"), "\n★≫\u{20D2}");
+}
+
+#[test]
+fn decode_entities_decodes_legacy_references_without_semicolon() {
+    assert_eq!(decode_entities("AT&ampT &copy 2019"), "AT&T © 2019");
+    // The longest legacy reference wins.
+    assert_eq!(decode_entities("&notit;"), "¬it;");
+    assert_eq!(decode_entities("&notin;"), "∉");
+}
+
+#[test]
+fn decode_entities_keeps_unknown_references() {
+    assert_eq!(decode_entities("Fish & chips"), "Fish & chips");
+    assert_eq!(decode_entities("&unknown; &#; &#x; &"), "&unknown; &#; &#x; &");
+    assert_eq!(decode_entities("&&amp;"), "&&");
+}
+
+#[test]
+fn decode_attribute_keeps_legacy_references_followed_by_alphanumerics() {
+    assert_eq!(decode_attribute("AT&amp;T"), "AT&T");
+    assert_eq!(decode_attribute("?a=1&copy=2&ampx"), "?a=1&copy=2&ampx");
+    assert_eq!(decode_attribute("&copy 2019&amp"), "© 2019&");
+}
+
+#[test]
+fn collapse_whitespace_is_working() {
+    assert_eq!(collapse_whitespace(""), "");
+    assert_eq!(collapse_whitespace("  \n "), "");
+    assert_eq!(
+        collapse_whitespace(" Montreal\n is\t\ta  city\u{A0}in Quebec. "),
+        "Montreal is a city in Quebec."
+    );
+}
@@ -1,7 +1,15 @@
 extern crate rayon;
+#[macro_use]
+extern crate serde_json;
+extern crate unicode_normalization;
 pub mod story_builder {
     pub mod article_provider;
+    pub mod exclusion;
+    pub mod story;
     pub mod story_builder;
 }
 
+pub mod html;
+pub mod title;
+pub mod wikitext;
 pub mod xml_wiki_parser;
@@ -0,0 +1,382 @@
+use story_builder::article_provider::*;
+use title::TitleKey;
+use serde_json::{self, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// Default time after which a cached article is fetched again from the inner provider (a week).
+const DEFAULT_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+/// Default maximum size of the cache directory, in bytes (100 MB).
+const DEFAULT_MAX_SIZE: u64 = 100 * 1024 * 1024;
+
+struct CachedArticle {
+    paragraphs: Vec<Paragraph>,
+    topic: String,
+    disambiguation: bool,
+}
+
+impl Article for CachedArticle {
+    fn get_paragraphs(&self) -> &Vec<Paragraph> {
+        &self.paragraphs
+    }
+    fn get_topic(&self) -> &str {
+        &self.topic
+    }
+    fn is_disambiguation(&self) -> bool {
+        self.disambiguation
+    }
+}
+
+/// Wraps an `ArticleProvider` and keeps the paragraphs of the articles it provides in a directory,
+/// so that the articles fetched by previous runs are read from the disk instead of being fetched
+/// again. Only `get` is cached; `search` and `backlinks` always go to the inner provider.
+pub struct CachingArticleProvider<P: ArticleProvider> {
+    inner: P,
+    cache_dir: PathBuf,
+    ttl: Duration,
+    max_size: u64,
+    /// Total size of the entries in the cache directory, in bytes.
+    size: Mutex<u64>,
+    /// Number of entries written so far, used to give each temporary file its own name.
+    writes: AtomicUsize,
+}
+
+impl<P: ArticleProvider> CachingArticleProvider<P> {
+    /// Caches the articles of the `inner` provider in `cache_dir`, which is created if needed.
+    /// Entries expire after a week, and the cache is kept under 100 MB by default. The cache
+    /// directory may hold other files; only the entries of the cache count, and are ever removed.
+    pub fn new<Q: AsRef<Path>>(inner: P, cache_dir: Q) -> io::Result<CachingArticleProvider<P>> {
+        let cache_dir = cache_dir.as_ref().to_path_buf();
+        fs::create_dir_all(&cache_dir)?;
+        let size = cache_entries(&cache_dir)?.iter().map(|&(_, len, _)| len).sum();
+        Ok(CachingArticleProvider {
+            inner,
+            cache_dir,
+            ttl: Duration::from_secs(DEFAULT_TTL_SECS),
+            max_size: DEFAULT_MAX_SIZE,
+            size: Mutex::new(size),
+            writes: AtomicUsize::new(0),
+        })
+    }
+
+    /// Cached articles older than the `ttl` are fetched again from the inner provider.
+    pub fn ttl(mut self, ttl: Duration) -> CachingArticleProvider<P> {
+        self.ttl = ttl;
+        self
+    }
+
+    /// The oldest entries are removed whenever the cache grows larger than `max_size` bytes.
+    pub fn max_size(mut self, max_size: u64) -> CachingArticleProvider<P> {
+        self.max_size = max_size;
+        self
+    }
+
+    fn cache_path(&self, key: &str) -> PathBuf {
+        self.cache_dir.join(format!("{:016x}.json", hash_key(key)))
+    }
+
+    /// Read the article cached for the `key`, unless it is missing, expired or unreadable.
+    fn read_cached(&self, key: &str) -> Option<CachedArticle> {
+        let path = self.cache_path(key);
+        let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+        // An entry written "in the future" (the clock went back) is considered fresh.
+        if let Ok(age) = modified.elapsed() {
+            if age >= self.ttl {
+                return None;
+            }
+        }
+        let json: Value = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+        // Different keys may share a file name; only use the entry written for this key.
+        if json["key"] != key {
+            return None;
+        }
+        let mut paragraphs = vec![];
+        for paragraph in json["paragraphs"].as_array()? {
+            paragraphs.push(Paragraph {
+                text: paragraph["text"].as_str()?.to_owned(),
+                topics: strings(&paragraph["topics"])?,
+                kind: ParagraphKind::from_name(paragraph["kind"].as_str()?)?,
+                // Entries written before sections and kinds were kept have none; they are fetched again.
+                section: strings(&paragraph["section"])?,
+                index: paragraph["index"].as_u64()? as usize,
+            });
+        }
+        Some(CachedArticle {
+            paragraphs,
+            topic: json["topic"].as_str()?.to_owned(),
+            // Entries written before disambiguation pages were flagged are fetched again too.
+            disambiguation: json["disambiguation"].as_bool()?,
+        })
+    }
+
+    /// Write the `article` in the cache for the `key`, then remove the oldest entries
+    /// if the cache grew too large.
+    fn write_cached(&self, key: &str, article: &ThreadedArticle) -> io::Result<()> {
+        let paragraphs: Vec<Value> = article
+            .get_paragraphs()
+            .iter()
+            .map(|paragraph| {
+                json!({
+                    "text": paragraph.text,
+                    "topics": paragraph.topics,
+                    "kind": paragraph.kind.name(),
+                    "section": paragraph.section,
+                    "index": paragraph.index,
+                })
+            })
+            .collect();
+        let contents = json!({
+            "key": key,
+            "topic": article.get_topic(),
+            "disambiguation": article.is_disambiguation(),
+            "paragraphs": paragraphs,
+        }).to_string();
+        // Write a temporary file first, so that no one ever reads a partially written entry.
+        let tmp_path = self.cache_dir.join(format!(
+            "{}-{}.tmp",
+            process::id(),
+            self.writes.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::write(&tmp_path, &contents)?;
+        let path = self.cache_path(key);
+        let replaced_len = fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or(0);
+        fs::rename(&tmp_path, &path)?;
+
+        let mut size = self.size.lock().unwrap();
+        *size = (*size + contents.len() as u64).saturating_sub(replaced_len);
+        if *size > self.max_size {
+            *size = self.evict(&path)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the least recently written entries (except the one at `keep_path`) until the cache
+    /// fits in its maximum size, and return its new size.
+    fn evict(&self, keep_path: &Path) -> io::Result<u64> {
+        let mut entries = cache_entries(&self.cache_dir)?;
+        let mut size = entries.iter().map(|&(_, len, _)| len).sum();
+        entries.sort();
+        for (_, len, path) in entries {
+            if size <= self.max_size {
+                break;
+            }
+            if path != keep_path {
+                fs::remove_file(path)?;
+                size -= len;
+            }
+        }
+        Ok(size)
+    }
+}
+
+impl<P: ArticleProvider> ArticleProvider for CachingArticleProvider<P> {
+    fn get(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, ProviderError> {
+        let key = TitleKey::new(topic);
+        if let Some(article) = self.read_cached(key.as_str()) {
+            return Ok(Some(Box::new(article)));
+        }
+        let article = self.inner.get(topic)?;
+        if let Some(ref article) = article {
+            // The cache only saves fetches; the article is still good if it cannot be written.
+            let _ = self.write_cached(key.as_str(), &**article);
+        }
+        Ok(article)
+    }
+
+    fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        self.inner.search(topic)
+    }
+
+    fn supports_backlinks(&self) -> bool {
+        self.inner.supports_backlinks()
+    }
+
+    fn backlinks(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        self.inner.backlinks(topic)
+    }
+}
+
+/// The last modification time, size and path of each entry of the `cache_dir`. Entries are named
+/// after the hash of their key, such as "cbf29ce484222325.json"; other files are left out, including
+/// the temporary files being written.
+fn cache_entries(cache_dir: &Path) -> io::Result<Vec<(SystemTime, u64, PathBuf)>> {
+    let mut entries = vec![];
+    for entry in fs::read_dir(cache_dir)? {
+        let entry = entry?;
+        if !is_entry_name(&entry.file_name().to_string_lossy()) {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        if metadata.is_file() {
+            entries.push((metadata.modified()?, metadata.len(), entry.path()));
+        }
+    }
+    Ok(entries)
+}
+
+/// Whether the `file_name` is the name of a cache entry, as given by `cache_path`.
+fn is_entry_name(file_name: &str) -> bool {
+    file_name.len() == 21
+        && file_name.ends_with(".json")
+        && file_name[..16].bytes().all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase())
+}
+
+/// The strings of a JSON array, or `None` if the `value` is not an array of strings.
+fn strings(value: &Value) -> Option<Vec<String>> {
+    let mut strings = vec![];
+    for string in value.as_array()? {
+        strings.push(string.as_str()?.to_owned());
+    }
+    Some(strings)
+}
+
+/// 64-bit FNV-1a hash of the `key`, used to name its cache file. Unlike the hashers of the
+/// standard library, it is guaranteed to stay the same from one build to the next.
+fn hash_key(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// Serves an article with a single paragraph for every topic but "Unknown",
+    /// and counts the calls to `get`. Topics ending with "(disambiguation)" are disambiguation pages.
+    struct CountingProvider {
+        gets: AtomicUsize,
+    }
+
+    impl ArticleProvider for CountingProvider {
+        fn get(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, ProviderError> {
+            self.gets.fetch_add(1, Ordering::SeqCst);
+            if topic == "Unknown" {
+                return Ok(None);
+            }
+            Ok(Some(Box::new(CachedArticle {
+                paragraphs: vec![Paragraph {
+                    text: format!("The {} is a small carnivorous mammal.", topic),
+                    topics: vec!["Carnivore".to_owned(), "mammal".to_owned()],
+                    kind: ParagraphKind::ListItem,
+                    section: vec!["Biology".to_owned(), "Diet".to_owned()],
+                    index: 3,
+                }],
+                topic: topic.to_owned(),
+                disambiguation: topic.ends_with("(disambiguation)"),
+            })))
+        }
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+            Ok(vec![topic.to_owned()])
+        }
+    }
+
+    fn counting_provider() -> CountingProvider {
+        CountingProvider {
+            gets: AtomicUsize::new(0),
+        }
+    }
+
+    /// An empty cache directory for the test `name`.
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("wikistory_cache_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn get_is_served_from_cache() {
+        let dir = cache_dir("served");
+        let provider = CachingArticleProvider::new(counting_provider(), &dir).unwrap();
+        provider.get("Cat").unwrap().expect("Expected Some, got None.");
+        let article = provider.get("cat_").unwrap().expect("Expected Some, got None.");
+        assert_eq!(provider.inner.gets.load(Ordering::SeqCst), 1);
+        assert_eq!(article.get_topic(), "Cat");
+        assert_eq!(
+            article.get_paragraphs()[0].text,
+            "The Cat is a small carnivorous mammal."
+        );
+        assert_eq!(article.get_paragraphs()[0].topics, vec!["Carnivore", "mammal"]);
+        assert_eq!(article.get_paragraphs()[0].section, vec!["Biology", "Diet"]);
+        assert_eq!(article.get_paragraphs()[0].index, 3);
+        assert_eq!(article.get_paragraphs()[0].kind, ParagraphKind::ListItem);
+
+        // The cache is kept between runs.
+        let provider = CachingArticleProvider::new(counting_provider(), &dir).unwrap();
+        provider.get("cat").unwrap().expect("Expected Some, got None.");
+        assert_eq!(provider.inner.gets.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn get_keeps_disambiguation_pages_flagged() {
+        let dir = cache_dir("disambiguation");
+        let provider = CachingArticleProvider::new(counting_provider(), &dir).unwrap();
+        assert!(!provider.get("Cat").unwrap().unwrap().is_disambiguation());
+        assert!(provider.get("Cat (disambiguation)").unwrap().unwrap().is_disambiguation());
+
+        let provider = CachingArticleProvider::new(counting_provider(), &dir).unwrap();
+        assert!(!provider.get("Cat").unwrap().unwrap().is_disambiguation());
+        assert!(provider.get("Cat (disambiguation)").unwrap().unwrap().is_disambiguation());
+        assert_eq!(provider.inner.gets.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn get_missing_article_is_not_cached() {
+        let provider = CachingArticleProvider::new(counting_provider(), cache_dir("missing")).unwrap();
+        assert!(provider.get("Unknown").unwrap().is_none());
+        assert!(provider.get("Unknown").unwrap().is_none());
+        assert_eq!(provider.inner.gets.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn expired_entries_are_fetched_again() {
+        let provider = CachingArticleProvider::new(counting_provider(), cache_dir("expired"))
+            .unwrap()
+            .ttl(Duration::from_secs(0));
+        provider.get("Cat").unwrap();
+        provider.get("Cat").unwrap();
+        assert_eq!(provider.inner.gets.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn cache_is_kept_under_max_size() {
+        let dir = cache_dir("max_size");
+        let provider = CachingArticleProvider::new(counting_provider(), &dir)
+            .unwrap()
+            .max_size(0);
+        provider.get("Cat").unwrap();
+        provider.get("Dog").unwrap();
+        // Only the entry just written is kept; the other one was removed.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        provider.get("Dog").unwrap();
+        provider.get("Cat").unwrap();
+        assert_eq!(provider.inner.gets.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn cache_leaves_other_files_alone() {
+        let dir = cache_dir("other_files");
+        fs::create_dir_all(&dir).unwrap();
+        let foreign_files = ["notes.txt", "123-0.tmp", "CBF29CE484222325.json"];
+        for name in &foreign_files {
+            fs::write(dir.join(name), "Not an entry of the cache, and larger than the maximum size.").unwrap();
+        }
+        // The other files do not count towards the size of the cache either.
+        let provider = CachingArticleProvider::new(counting_provider(), &dir).unwrap();
+        assert_eq!(*provider.size.lock().unwrap(), 0);
+
+        let provider = provider.max_size(0);
+        provider.get("Cat").unwrap();
+        provider.get("Dog").unwrap();
+        for name in &foreign_files {
+            assert!(dir.join(name).exists(), "{} was removed", name);
+        }
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), foreign_files.len() + 1);
+    }
+}
@@ -0,0 +1,353 @@
+use story_builder::article_provider::*;
+use story_builder::article_provider::http_article_provider::HTTPArticleProvider;
+use serde_json::{self, Value};
+use std::collections::HashSet;
+use wikitext::{extract_paragraphs, extract_paragraphs_with_lists, is_disambiguation};
+
+/// Maximum number of suggestions returned by `search`.
+const MAX_SEARCH_RESULTS: usize = 10;
+
+struct ApiArticle {
+    paragraphs: Vec<Paragraph>,
+    topic: String,
+    disambiguation: bool,
+}
+
+impl Article for ApiArticle {
+    fn get_paragraphs(&self) -> &Vec<Paragraph> {
+        &self.paragraphs
+    }
+    fn get_topic(&self) -> &str {
+        &self.topic
+    }
+    fn is_disambiguation(&self) -> bool {
+        self.disambiguation
+    }
+}
+
+/// Provides articles from a MediaWiki site through its Action API (`api.php`). Unlike
+/// `HTTPArticleProvider`, it does not depend on the HTML of the site's skin: articles are
+/// parsed from their wikitext, and the API resolves redirects to canonical titles itself.
+pub struct MediaWikiApiProvider {
+    api_uri: String,
+}
+
+impl MediaWikiApiProvider {
+    /// Provides articles from the English Wikipedia.
+    pub fn new() -> MediaWikiApiProvider {
+        MediaWikiApiProvider::with_language("en")
+    }
+    /// Provides articles from the `lang` edition of Wikipedia (`en`, `fr`, `de`, ...).
+    pub fn with_language(lang: &str) -> MediaWikiApiProvider {
+        MediaWikiApiProvider::with_base_url(&format!("https://{}.wikipedia.org", lang))
+    }
+    /// Provides articles from the MediaWiki site found at `base_url` (e.g. `http://localhost:8080`),
+    /// which must serve its API at `/w/api.php`.
+    pub fn with_base_url(base_url: &str) -> MediaWikiApiProvider {
+        MediaWikiApiProvider {
+            api_uri: format!("{}/w/api.php", base_url.trim_end_matches('/')),
+        }
+    }
+
+    /// Call the API with the `params` (`name=value` pairs, joined by `&`) and return its answer.
+    fn query(&self, params: &str) -> Result<Value, ProviderError> {
+        let uri = format!("{}?format=json&formatversion=2&{}", self.api_uri, params);
+        let content = HTTPArticleProvider::fetch(&uri)?.ok_or_else(|| ProviderError::HttpStatus {
+            uri: uri.clone(),
+            status: 404,
+        })?;
+        serde_json::from_str(&content)
+            .map_err(|err| ProviderError::InvalidResponse(format!("{} for URL: {}", err, uri)))
+    }
+
+    /// Build the article from the answer of `action=parse`, or `None` if there is no such page.
+    fn article_from_parse(json: &Value) -> Result<Option<Box<ThreadedArticle>>, ProviderError> {
+        if let Some(code) = json["error"]["code"].as_str() {
+            return match code {
+                "missingtitle" | "invalidtitle" => Ok(None),
+                _ => Err(MediaWikiApiProvider::api_error(json)),
+            };
+        }
+        // Since redirects are followed, this is the canonical title of the article.
+        let topic = json["parse"]["title"].as_str();
+        let wikitext = json["parse"]["wikitext"].as_str();
+        match (topic, wikitext) {
+            (Some(topic), Some(wikitext)) => {
+                // The candidates of disambiguation pages are listed; keep them.
+                let disambiguation = is_disambiguation(wikitext);
+                Ok(Some(Box::new(ApiArticle {
+                    paragraphs: if disambiguation {
+                        extract_paragraphs_with_lists(wikitext)
+                    } else {
+                        extract_paragraphs(wikitext)
+                    },
+                    topic: topic.to_owned(),
+                    disambiguation,
+                })))
+            }
+            _ => Err(ProviderError::InvalidResponse(
+                "Missing title or wikitext in parse result".to_owned(),
+            )),
+        }
+    }
+
+    /// Extract the titles of the `list` items found in the `query` of the answer.
+    fn titles(json: &Value, list: &Value) -> Result<Vec<String>, ProviderError> {
+        if json["error"].is_object() {
+            return Err(MediaWikiApiProvider::api_error(json));
+        }
+        Ok(list.as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item["title"].as_str().map(str::to_owned))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// The pages linking to the `title`, along with whether each of them is a redirect. Long lists
+    /// come in several answers, each continuing the previous one; all of them are requested.
+    fn links_here(&self, title: &str) -> Result<Vec<(String, bool)>, ProviderError> {
+        let mut pages = vec![];
+        let mut continuation = String::new();
+        loop {
+            let json = self.query(&format!(
+                "action=query&prop=linkshere&lhprop=title%7Credirect&lhnamespace=0&lhlimit=500&redirects=1&titles={}{}",
+                MediaWikiApiProvider::encode(title),
+                continuation
+            ))?;
+            if json["error"].is_object() {
+                return Err(MediaWikiApiProvider::api_error(&json));
+            }
+            for page in json["query"]["pages"][0]["linkshere"].as_array().into_iter().flatten() {
+                if let Some(title) = page["title"].as_str() {
+                    pages.push((title.to_owned(), page["redirect"].as_bool().unwrap_or(false)));
+                }
+            }
+            // The parameters to send back to get the rest of the list, such as `lhcontinue`:
+            match json["continue"].as_object() {
+                Some(params) => {
+                    continuation = params
+                        .iter()
+                        .map(|(name, value)| {
+                            format!("&{}={}", name, MediaWikiApiProvider::encode(value.as_str().unwrap_or("")))
+                        })
+                        .collect()
+                }
+                None => return Ok(pages),
+            }
+        }
+    }
+
+    fn api_error(json: &Value) -> ProviderError {
+        ProviderError::InvalidResponse(format!(
+            "API error {}: {}",
+            json["error"]["code"].as_str().unwrap_or("unknown"),
+            json["error"]["info"].as_str().unwrap_or("")
+        ))
+    }
+
+    /// Percent-encode the `value` of a query parameter.
+    fn encode(value: &str) -> String {
+        let mut encoded = String::with_capacity(value.len());
+        for byte in value.bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                    encoded.push(byte as char)
+                }
+                _ => encoded.push_str(&format!("%{:02X}", byte)),
+            }
+        }
+        encoded
+    }
+}
+
+impl ArticleProvider for MediaWikiApiProvider {
+    fn get(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, ProviderError> {
+        if topic == "" {
+            return Ok(None); // Do not even try if the topic is empty.
+        }
+        let json = self.query(&format!(
+            "action=parse&prop=wikitext&redirects=1&page={}",
+            MediaWikiApiProvider::encode(topic)
+        ))?;
+        MediaWikiApiProvider::article_from_parse(&json)
+    }
+
+    fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        let json = self.query(&format!(
+            "action=query&list=search&srnamespace=0&srlimit={}&srsearch={}",
+            MAX_SEARCH_RESULTS,
+            MediaWikiApiProvider::encode(topic)
+        ))?;
+        MediaWikiApiProvider::titles(&json, &json["query"]["search"])
+    }
+
+    fn supports_backlinks(&self) -> bool {
+        true
+    }
+
+    fn backlinks(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        // Redirects to the topic have no paragraphs, but the pages linking to them are backlinks
+        // too (like MediaWiki, double redirects are not followed).
+        let mut backlinks = vec![];
+        for (title, redirect) in self.links_here(topic)? {
+            if redirect {
+                for (title, redirect) in self.links_here(&title)? {
+                    if !redirect {
+                        backlinks.push(title);
+                    }
+                }
+            } else {
+                backlinks.push(title);
+            }
+        }
+        let mut seen = HashSet::new();
+        backlinks.retain(|title| seen.insert(title.clone()));
+        Ok(backlinks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use story_builder::article_provider::mock_server::MockServer;
+    use story_builder::story::render_plain_text;
+    use story_builder::story_builder::StoryBuilder;
+    use std::sync::Arc;
+
+    static PARSE_MONTREAL: &'static str = r#"{"parse":{"title":"Montreal","pageid":7954681,
+        "redirects":[{"from":"Montreal city","to":"Montreal"}],
+        "wikitext":"{{Infobox settlement|name=Montreal}}\n'''Montreal''' is the most populous city in [[Quebec]].\n\nIt is the second-most populous city in [[Canada]]."}}"#;
+    static PARSE_CANADA: &'static str = r#"{"parse":{"title":"Canada","pageid":5042916,
+        "wikitext":"'''Canada''' is a country in [[North America]].\n\nIts capital is [[Ottawa]]."}}"#;
+    static PARSE_OTTAWA: &'static str = r#"{"parse":{"title":"Ottawa","pageid":22219,
+        "wikitext":"'''Ottawa''' is the capital city of Canada."}}"#;
+    static PARSE_MISSING: &'static str = r#"{"error":{"code":"missingtitle",
+        "info":"The page you specified doesn't exist."}}"#;
+    static SEARCH_MONTREAL: &'static str = r#"{"batchcomplete":true,"query":{"searchinfo":{"totalhits":2},
+        "search":[{"ns":0,"title":"Montreal","pageid":7954681},{"ns":0,"title":"Montreal Canadiens","pageid":76254}]}}"#;
+    static LINKSHERE_OTTAWA: &'static str = r#"{"batchcomplete":true,"query":{"pages":[{"pageid":22219,"ns":0,
+        "title":"Ottawa","linkshere":[{"title":"Canada"},{"title":"Gatineau"}]}]}}"#;
+    static LINKSHERE_OTTAWA_FIRST: &'static str = r#"{"continue":{"lhcontinue":"0|5042916","continue":"||"},
+        "query":{"pages":[{"pageid":22219,"ns":0,"title":"Ottawa",
+        "linkshere":[{"title":"Canada"},{"title":"Ottawa, Ontario","redirect":true}]}]}}"#;
+    static LINKSHERE_OTTAWA_REST: &'static str = r#"{"batchcomplete":true,"query":{"pages":[{"pageid":22219,"ns":0,
+        "title":"Ottawa","linkshere":[{"title":"Gatineau"}]}]}}"#;
+    static LINKSHERE_OTTAWA_ONTARIO: &'static str = r#"{"batchcomplete":true,"query":{"pages":[{"pageid":1022565,
+        "ns":0,"title":"Ottawa, Ontario","redirect":true,
+        "linkshere":[{"title":"Rideau Canal"},{"title":"Canada"},{"title":"Ottawa (city)","redirect":true}]}]}}"#;
+
+    fn parse_path(page: &str) -> String {
+        format!("/w/api.php?format=json&formatversion=2&action=parse&prop=wikitext&redirects=1&page={}", page)
+    }
+
+    fn linkshere_path(title: &str, continuation: &str) -> String {
+        format!(
+            "/w/api.php?format=json&formatversion=2&action=query&prop=linkshere&lhprop=title%7Credirect\
+             &lhnamespace=0&lhlimit=500&redirects=1&titles={}{}",
+            MediaWikiApiProvider::encode(title),
+            continuation
+        )
+    }
+
+    #[test]
+    fn encode_is_working() {
+        assert_eq!(MediaWikiApiProvider::encode("Montreal city"), "Montreal%20city");
+        assert_eq!(MediaWikiApiProvider::encode("AT&T"), "AT%26T");
+        assert_eq!(MediaWikiApiProvider::encode("Montréal"), "Montr%C3%A9al");
+    }
+
+    #[test]
+    fn get_follows_redirects() {
+        let server = MockServer::start(vec![(&parse_path("Montreal%20city"), PARSE_MONTREAL)]);
+        let provider = MediaWikiApiProvider::with_base_url(&server.base_url);
+        let article = provider.get("Montreal city").unwrap().expect("Expected Some, got None.");
+        assert_eq!(article.get_topic(), "Montreal");
+        let paragraphs = article.get_paragraphs();
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(paragraphs[0].text, "Montreal is the most populous city in Quebec.");
+        assert_eq!(paragraphs[0].topics, vec!["Quebec"]);
+    }
+
+    #[test]
+    fn get_missing_returns_none() {
+        let server = MockServer::start(vec![(&parse_path("Nowhere"), PARSE_MISSING)]);
+        let provider = MediaWikiApiProvider::with_base_url(&server.base_url);
+        match provider.get("Nowhere") {
+            Ok(None) => (),
+            Ok(Some(_)) => panic!("Expected None, got Some."),
+            Err(err) => panic!("Expected None, got error: {}", err),
+        }
+    }
+
+    #[test]
+    fn get_invalid_answer_is_an_error() {
+        let server = MockServer::start(vec![(&parse_path("Montreal"), "<html>Not JSON</html>")]);
+        let provider = MediaWikiApiProvider::with_base_url(&server.base_url);
+        match provider.get("Montreal") {
+            Err(ProviderError::InvalidResponse(_)) => (),
+            Err(err) => panic!("Expected an invalid response, got error: {}", err),
+            Ok(_) => panic!("Expected an invalid response, got Ok."),
+        }
+    }
+
+    #[test]
+    fn search_returns_titles() {
+        let server = MockServer::start(vec![(
+            "/w/api.php?format=json&formatversion=2&action=query&list=search&srnamespace=0&srlimit=10&srsearch=montreal",
+            SEARCH_MONTREAL,
+        )]);
+        let provider = MediaWikiApiProvider::with_base_url(&server.base_url);
+        assert_eq!(
+            provider.search("montreal").unwrap(),
+            vec!["Montreal", "Montreal Canadiens"]
+        );
+    }
+
+    #[test]
+    fn backlinks_returns_titles() {
+        let server = MockServer::start(vec![(&linkshere_path("Ottawa", ""), LINKSHERE_OTTAWA)]);
+        let provider = MediaWikiApiProvider::with_base_url(&server.base_url);
+        assert_eq!(provider.backlinks("Ottawa").unwrap(), vec!["Canada", "Gatineau"]);
+    }
+
+    #[test]
+    fn backlinks_follow_continuations() {
+        let server = MockServer::start(vec![
+            (&linkshere_path("Ottawa", ""), LINKSHERE_OTTAWA_FIRST),
+            (
+                &linkshere_path("Ottawa", "&continue=%7C%7C&lhcontinue=0%7C5042916"),
+                LINKSHERE_OTTAWA_REST,
+            ),
+            (&linkshere_path("Ottawa, Ontario", ""), LINKSHERE_OTTAWA_ONTARIO),
+        ]);
+        let provider = MediaWikiApiProvider::with_base_url(&server.base_url);
+        // The pages linking to the redirect are included (once), but not the redirects themselves.
+        assert_eq!(
+            provider.backlinks("Ottawa").unwrap(),
+            vec!["Canada", "Rideau Canal", "Gatineau"]
+        );
+    }
+
+    #[test]
+    fn build_story_from_api() {
+        let server = MockServer::start(vec![
+            (&parse_path("Montreal"), PARSE_MONTREAL),
+            (&parse_path("Ottawa"), PARSE_OTTAWA),
+            (&parse_path("Canada"), PARSE_CANADA),
+            (&linkshere_path("Ottawa", ""), LINKSHERE_OTTAWA),
+        ]);
+        let provider = MediaWikiApiProvider::with_base_url(&server.base_url);
+        let mut story_builder = StoryBuilder::new(Arc::new(provider));
+        assert_eq!(
+            story_builder.build_story("Montreal", "Ottawa").map(|story| render_plain_text(&story)),
+            Ok(
+                "-> (Montreal to Canada)\r\nIt is the second-most populous city in Canada.\r\n\
+                 -> (Canada to Ottawa)\r\nIts capital is Ottawa.\r\n"
+                    .to_owned()
+            )
+        );
+    }
+}
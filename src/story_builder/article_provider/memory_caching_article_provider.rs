@@ -0,0 +1,375 @@
+use story_builder::article_provider::*;
+use title::TitleKey;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+
+/// Default maximum number of articles kept in memory.
+const DEFAULT_CAPACITY: usize = 1000;
+
+type FetchResult = Result<Option<Arc<ThreadedArticle>>, ProviderError>;
+
+/// An article kept in memory; every `get` of the same topic shares it.
+struct SharedArticle(Arc<ThreadedArticle>);
+
+impl Article for SharedArticle {
+    fn get_paragraphs(&self) -> &Vec<Paragraph> {
+        self.0.get_paragraphs()
+    }
+    fn get_topic(&self) -> &str {
+        self.0.get_topic()
+    }
+    fn is_disambiguation(&self) -> bool {
+        self.0.is_disambiguation()
+    }
+}
+
+/// A fetch from the inner provider that other threads asking for the same topic wait for.
+struct InFlight {
+    result: Mutex<Option<FetchResult>>,
+    done: Condvar,
+}
+
+impl InFlight {
+    fn wait(&self) -> FetchResult {
+        let mut result = self.result.lock().unwrap();
+        while result.is_none() {
+            result = self.done.wait(result).unwrap();
+        }
+        result.clone().unwrap()
+    }
+}
+
+/// Resolves a fetch from the inner provider once dropped, so that the threads waiting for it are
+/// never left hanging; if the inner provider panics, they get an error instead of its `result`.
+struct FetchGuard<'a> {
+    cache: &'a Mutex<Cache>,
+    capacity: usize,
+    topic: &'a str,
+    key: TitleKey,
+    in_flight: &'a InFlight,
+    result: Option<FetchResult>,
+}
+
+impl<'a> Drop for FetchGuard<'a> {
+    fn drop(&mut self) {
+        let topic = self.topic;
+        let result = self.result.take().unwrap_or_else(|| {
+            Err(ProviderError::InvalidResponse(format!(
+                "The article provider panicked while fetching <{}>.",
+                topic
+            )))
+        });
+        // This may run while unwinding, where panicking again would abort.
+        {
+            let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+            cache.in_flight.remove(&self.key);
+            // Articles that do not exist and errors are not kept; the next `get` tries again.
+            if let Ok(Some(ref article)) = result {
+                cache.insert(self.key.clone(), article.clone(), self.capacity);
+            }
+        }
+        *self.in_flight.result.lock().unwrap_or_else(PoisonError::into_inner) = Some(result);
+        self.in_flight.done.notify_all();
+    }
+}
+
+/// The articles kept in memory, along with the fetches in progress.
+struct Cache {
+    /// Maps a cache key to its article and the tick of its last use.
+    articles: HashMap<TitleKey, (Arc<ThreadedArticle>, u64)>,
+    /// Maps the tick of the last use of each article to its cache key, oldest first.
+    uses: BTreeMap<u64, TitleKey>,
+    /// Incremented on each use of an article.
+    tick: u64,
+    in_flight: HashMap<TitleKey, Arc<InFlight>>,
+}
+
+impl Cache {
+    /// Get the article cached for the `key`, marking it as the most recently used.
+    fn get(&mut self, key: &TitleKey) -> Option<Arc<ThreadedArticle>> {
+        self.tick += 1;
+        let tick = self.tick;
+        let entry = self.articles.get_mut(key)?;
+        self.uses.remove(&entry.1);
+        self.uses.insert(tick, key.clone());
+        entry.1 = tick;
+        Some(entry.0.clone())
+    }
+
+    /// Keep the `article` for the `key`, removing the least recently used articles beyond the `capacity`.
+    fn insert(&mut self, key: TitleKey, article: Arc<ThreadedArticle>, capacity: usize) {
+        self.tick += 1;
+        if let Some((_, last_use)) = self.articles.insert(key.clone(), (article, self.tick)) {
+            self.uses.remove(&last_use);
+        }
+        self.uses.insert(self.tick, key);
+        while self.articles.len() > capacity {
+            let oldest = *self.uses.keys().next().unwrap();
+            let oldest_key = self.uses.remove(&oldest).unwrap();
+            self.articles.remove(&oldest_key);
+        }
+    }
+}
+
+/// Wraps an `ArticleProvider` and keeps the most recently used articles in memory, so that
+/// the stories built in the same process share them. Threads asking for a topic that is
+/// already being fetched wait for that fetch instead of fetching the topic again.
+/// Only `get` is cached; `search` and `backlinks` always go to the inner provider.
+pub struct MemoryCachingArticleProvider<P: ArticleProvider> {
+    inner: P,
+    capacity: usize,
+    cache: Mutex<Cache>,
+}
+
+impl<P: ArticleProvider> MemoryCachingArticleProvider<P> {
+    /// Keeps up to 1000 articles of the `inner` provider in memory.
+    pub fn new(inner: P) -> MemoryCachingArticleProvider<P> {
+        MemoryCachingArticleProvider {
+            inner,
+            capacity: DEFAULT_CAPACITY,
+            cache: Mutex::new(Cache {
+                articles: HashMap::new(),
+                uses: BTreeMap::new(),
+                tick: 0,
+                in_flight: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Keep at most `capacity` articles in memory.
+    pub fn capacity(mut self, capacity: usize) -> MemoryCachingArticleProvider<P> {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Fetch the `topic` from the inner provider for every thread waiting on the `in_flight` fetch.
+    fn fetch(&self, topic: &str, key: TitleKey, in_flight: &InFlight) -> FetchResult {
+        let mut guard = FetchGuard {
+            cache: &self.cache,
+            capacity: self.capacity,
+            topic,
+            key,
+            in_flight,
+            result: None,
+        };
+        let result: FetchResult = self
+            .inner
+            .get(topic)
+            .map(|article| article.map(Arc::from));
+        guard.result = Some(result.clone());
+        result
+    }
+}
+
+impl<P: ArticleProvider> ArticleProvider for MemoryCachingArticleProvider<P> {
+    fn get(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, ProviderError> {
+        let key = TitleKey::new(topic);
+        let (in_flight, must_fetch) = {
+            let mut cache = self.cache.lock().unwrap();
+            if let Some(article) = cache.get(&key) {
+                return Ok(Some(Box::new(SharedArticle(article))));
+            }
+            match cache.in_flight.get(&key).cloned() {
+                Some(in_flight) => (in_flight, false),
+                None => {
+                    let in_flight = Arc::new(InFlight {
+                        result: Mutex::new(None),
+                        done: Condvar::new(),
+                    });
+                    cache.in_flight.insert(key.clone(), in_flight.clone());
+                    (in_flight, true)
+                }
+            }
+        };
+        let result = if must_fetch {
+            self.fetch(topic, key, &in_flight)
+        } else {
+            in_flight.wait()
+        };
+        result.map(|article| article.map(|article| Box::new(SharedArticle(article)) as Box<ThreadedArticle>))
+    }
+
+    fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        self.inner.search(topic)
+    }
+
+    fn supports_backlinks(&self) -> bool {
+        self.inner.supports_backlinks()
+    }
+
+    fn backlinks(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        self.inner.backlinks(topic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc::{channel, Receiver, Sender};
+    use std::thread;
+
+    struct TestArticle {
+        paragraphs: Vec<Paragraph>,
+        topic: String,
+    }
+
+    impl Article for TestArticle {
+        fn get_paragraphs(&self) -> &Vec<Paragraph> {
+            &self.paragraphs
+        }
+        fn get_topic(&self) -> &str {
+            &self.topic
+        }
+        fn is_disambiguation(&self) -> bool {
+            self.topic.ends_with("(disambiguation)")
+        }
+    }
+
+    /// Serves an empty article for every topic but "Unknown", once its `gate` (if any) lets it,
+    /// and counts the calls to `get`. Topics ending with "(disambiguation)" are disambiguation pages,
+    /// and "Panic" makes it panic.
+    struct GatedProvider {
+        gate: Option<Mutex<Receiver<()>>>,
+        gets: AtomicUsize,
+    }
+
+    impl ArticleProvider for GatedProvider {
+        fn get(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, ProviderError> {
+            self.gets.fetch_add(1, Ordering::SeqCst);
+            if let Some(ref gate) = self.gate {
+                gate.lock().unwrap().recv().unwrap();
+            }
+            if topic == "Unknown" {
+                return Ok(None);
+            }
+            if topic == "Panic" {
+                panic!("Unable to fetch <{}>.", topic);
+            }
+            Ok(Some(Box::new(TestArticle {
+                paragraphs: vec![],
+                topic: topic.to_owned(),
+            })))
+        }
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+            Ok(vec![topic.to_owned()])
+        }
+    }
+
+    fn provider() -> GatedProvider {
+        GatedProvider {
+            gate: None,
+            gets: AtomicUsize::new(0),
+        }
+    }
+
+    /// A provider whose `get` waits for the returned sender to open its gate.
+    fn gated_provider() -> (GatedProvider, Sender<()>) {
+        let (sender, receiver) = channel();
+        let provider = GatedProvider {
+            gate: Some(Mutex::new(receiver)),
+            gets: AtomicUsize::new(0),
+        };
+        (provider, sender)
+    }
+
+    /// Wait until `threads` threads share the fetch of the `topic`: one fetches it while the others
+    /// wait for it, and the cache holds it too.
+    fn wait_for_fetch(provider: &MemoryCachingArticleProvider<GatedProvider>, topic: &str, threads: usize) {
+        loop {
+            if let Some(in_flight) = provider.cache.lock().unwrap().in_flight.get(&TitleKey::new(topic)) {
+                if Arc::strong_count(in_flight) == threads + 1 {
+                    return;
+                }
+            }
+            thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn get_keeps_recently_used_articles() {
+        let provider = MemoryCachingArticleProvider::new(provider()).capacity(2);
+        provider.get("Cat").unwrap();
+        provider.get("Dog").unwrap();
+        // Cat is now more recently used than Dog, which is dropped to make room for Bird.
+        let article = provider.get("cat").unwrap().expect("Expected Some, got None.");
+        assert_eq!(article.get_topic(), "Cat");
+        provider.get("Bird").unwrap();
+        assert_eq!(provider.inner.gets.load(Ordering::SeqCst), 3);
+        provider.get("Cat").unwrap();
+        assert_eq!(provider.inner.gets.load(Ordering::SeqCst), 3);
+        provider.get("Dog").unwrap();
+        assert_eq!(provider.inner.gets.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn get_keeps_disambiguation_pages_flagged() {
+        let provider = MemoryCachingArticleProvider::new(provider());
+        // Once fetched from the inner provider, then from memory.
+        for _ in 0..2 {
+            assert!(provider.get("Mercury (disambiguation)").unwrap().unwrap().is_disambiguation());
+            assert!(!provider.get("Mercury").unwrap().unwrap().is_disambiguation());
+        }
+        assert_eq!(provider.inner.gets.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn get_missing_article_is_not_kept() {
+        let provider = MemoryCachingArticleProvider::new(provider());
+        assert!(provider.get("Unknown").unwrap().is_none());
+        assert!(provider.get("Unknown").unwrap().is_none());
+        assert_eq!(provider.inner.gets.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn concurrent_gets_share_a_single_fetch() {
+        let (inner, gate) = gated_provider();
+        let provider = Arc::new(MemoryCachingArticleProvider::new(inner));
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let provider = provider.clone();
+                thread::spawn(move || {
+                    provider
+                        .get("Cat")
+                        .unwrap()
+                        .expect("Expected Some, got None.")
+                        .get_topic()
+                        .to_owned()
+                })
+            })
+            .collect();
+        wait_for_fetch(&provider, "Cat", 8);
+        gate.send(()).unwrap();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), "Cat");
+        }
+        assert_eq!(provider.inner.gets.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn gets_waiting_for_a_panicking_fetch_fail() {
+        let (inner, gate) = gated_provider();
+        let provider = Arc::new(MemoryCachingArticleProvider::new(inner));
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let provider = provider.clone();
+                thread::spawn(move || provider.get("Panic").map(|article| article.is_some()))
+            })
+            .collect();
+        wait_for_fetch(&provider, "Panic", 4);
+        gate.send(()).unwrap();
+        let results: Vec<_> = threads.into_iter().map(|thread| thread.join()).collect();
+        // The thread fetching the article panics, while the others get an error instead of waiting forever.
+        assert_eq!(results.iter().filter(|result| result.is_err()).count(), 1);
+        for result in results.into_iter().filter_map(|result| result.ok()) {
+            assert_eq!(
+                result,
+                Err(ProviderError::InvalidResponse(
+                    "The article provider panicked while fetching <Panic>.".to_owned()
+                ))
+            );
+        }
+        assert_eq!(provider.inner.gets.load(Ordering::SeqCst), 1);
+        assert!(provider.cache.lock().unwrap().in_flight.is_empty());
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A local HTTP server serving canned pages, so that the providers fetching pages
+/// over HTTP can be tested without any network access.
+pub struct MockServer {
+    /// The URL of the server, without a trailing slash (`http://127.0.0.1:port`).
+    pub base_url: String,
+    /// The path (and query) of each request received so far.
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    /// Serve the `pages`, by path (and query), until the end of the test.
+    /// Every other path is answered with a 404.
+    pub fn start(pages: Vec<(&str, &str)>) -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let pages: HashMap<String, String> = pages
+            .into_iter()
+            .map(|(path, body)| (path.to_owned(), body.to_owned()))
+            .collect();
+        let requests = Arc::new(Mutex::new(vec![]));
+        let received = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                let _ = reader.read_line(&mut request_line);
+                // Skip the headers; the request has no body.
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).unwrap_or(0) == 0 || header == "\r\n" {
+                        break;
+                    }
+                }
+                let path = request_line.split_whitespace().nth(1).unwrap_or("").to_owned();
+                let response = match pages.get(&path) {
+                    Some(body) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\n\
+                         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    ),
+                    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_owned(),
+                };
+                received.lock().unwrap().push(path);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        MockServer { base_url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
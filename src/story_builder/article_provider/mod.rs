@@ -1,21 +1,153 @@
+use std::error::Error;
+use std::fmt;
+
 pub type ThreadedAP = (ArticleProvider + Send + Sync);
 pub type ThreadedArticle = (Article + Send + Sync);
 
+/// The reasons an `ArticleProvider` may fail to answer. An article that does not exist
+/// is not an error; `get` returns `Ok(None)` for those.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProviderError {
+    /// The request could not be completed (connection dropped, timeout, etc..).
+    Network(String),
+    /// The server answered with an unexpected HTTP status.
+    HttpStatus { uri: String, status: u16 },
+    /// The articles could not be read from their local source (missing or corrupted files).
+    Io(String),
+    /// The server answered with something that could not be understood, or with an error of its own.
+    InvalidResponse(String),
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProviderError::Network(ref msg) => write!(f, "Network error: {}", msg),
+            ProviderError::HttpStatus { ref uri, status } => {
+                write!(f, "HTTP status {} for URL: {}", status, uri)
+            }
+            ProviderError::Io(ref msg) => write!(f, "Unable to read articles: {}", msg),
+            ProviderError::InvalidResponse(ref msg) => write!(f, "Invalid response: {}", msg),
+        }
+    }
+}
+
+impl Error for ProviderError {
+    fn description(&self) -> &str {
+        "article provider error"
+    }
+}
+
+/// The kinds of elements of an article that paragraphs are extracted from.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParagraphKind {
+    /// A paragraph of prose; most articles are made of those.
+    Prose,
+    /// An item of a list (list articles, discographies, disambiguation pages, ...).
+    ListItem,
+    /// A cell of a table.
+    TableCell,
+    /// A row of an infobox, such as "Country: Canada".
+    InfoboxRow,
+}
+
+impl ParagraphKind {
+    /// Name of the kind, as written in JSON documents.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ParagraphKind::Prose => "prose",
+            ParagraphKind::ListItem => "list_item",
+            ParagraphKind::TableCell => "table_cell",
+            ParagraphKind::InfoboxRow => "infobox_row",
+        }
+    }
+
+    /// The kind with the given `name`, or `None` if there is no such kind.
+    pub fn from_name(name: &str) -> Option<ParagraphKind> {
+        match name {
+            "prose" => Some(ParagraphKind::Prose),
+            "list_item" => Some(ParagraphKind::ListItem),
+            "table_cell" => Some(ParagraphKind::TableCell),
+            "infobox_row" => Some(ParagraphKind::InfoboxRow),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ParagraphKind {
+    fn default() -> ParagraphKind {
+        ParagraphKind::Prose
+    }
+}
+
+#[derive(Default)]
 pub struct Paragraph {
     pub text: String,
     pub topics: Vec<String>,
+    /// The element of the article the paragraph comes from.
+    pub kind: ParagraphKind,
+    /// Headings of the sections holding the paragraph, outermost first (e.g. `["History", "20th century"]`).
+    /// Empty for the paragraphs of the lead section, before the first heading.
+    pub section: Vec<String>,
+    /// Position of the paragraph in its article, starting at 0.
+    pub index: usize,
+}
+
+impl Paragraph {
+    /// Returns true if the paragraph is part of the lead section of its article, which sums it up.
+    pub fn is_lead(&self) -> bool {
+        self.section.is_empty()
+    }
 }
 
 pub trait Article {
     fn get_paragraphs(&self) -> &Vec<Paragraph>;
     fn get_topic(&self) -> &str;
+    /// Returns true if the article is a disambiguation page, which only lists the articles
+    /// a topic may refer to ("Mercury (planet)", "Mercury (element)", ...).
+    fn is_disambiguation(&self) -> bool {
+        false
+    }
 }
 
 pub trait ArticleProvider {
     /// Returns a Some(Box<Article>) if the article is found, None otherwise.
-    fn get(&self, topic: &str) -> Option<Box<ThreadedArticle>>;
+    fn get(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, ProviderError>;
     /// Returns a Vector of topics that might be related to the topic entered.
-    fn search(&self, topic: &str) -> Vec<String>;
+    fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError>;
+    /// Returns true if this provider is able to list the articles linking to a topic.
+    fn supports_backlinks(&self) -> bool {
+        false
+    }
+    /// Returns a Vector of the topics of articles linking to the topic entered ("what links here").
+    /// Providers that do not support backlinks (see `supports_backlinks`) return nothing.
+    #[allow(unused_variables)]
+    fn backlinks(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        Ok(vec![])
+    }
+}
+
+/// Boxed providers are providers too, so that wrappers (such as `CachingArticleProvider`)
+/// can wrap a provider chosen at runtime.
+impl<P: ArticleProvider + ?Sized> ArticleProvider for Box<P> {
+    fn get(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, ProviderError> {
+        (**self).get(topic)
+    }
+    fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        (**self).search(topic)
+    }
+    fn supports_backlinks(&self) -> bool {
+        (**self).supports_backlinks()
+    }
+    fn backlinks(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        (**self).backlinks(topic)
+    }
 }
 
+pub mod caching_article_provider;
 pub mod http_article_provider;
+pub mod mediawiki_api_provider;
+pub mod memory_caching_article_provider;
+#[cfg(test)]
+mod mock_server;
+pub mod replay_article_provider;
+pub mod xml_dump_article_provider;
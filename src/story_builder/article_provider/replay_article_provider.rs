@@ -0,0 +1,208 @@
+use story_builder::article_provider::*;
+use story_builder::article_provider::http_article_provider::{HTTPArticleProvider, PageKind};
+use title::TitleKey;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Extension of the files holding a recorded page.
+const PAGE_EXTENSION: &'static str = "html";
+/// Extension of the files recording that there was no page (HTTP 404).
+const MISSING_EXTENSION: &'static str = "missing";
+
+/// Wraps an `HTTPArticleProvider` and records every page it fetches in the `fixtures_dir`,
+/// so that a `ReplayArticleProvider` can serve them back later without any network access.
+pub struct RecordingArticleProvider {
+    inner: HTTPArticleProvider,
+    fixtures_dir: PathBuf,
+}
+
+impl RecordingArticleProvider {
+    pub fn new<P: AsRef<Path>>(inner: HTTPArticleProvider, fixtures_dir: P) -> io::Result<RecordingArticleProvider> {
+        let fixtures_dir = fixtures_dir.as_ref().to_path_buf();
+        for kind in &[PageKind::Article, PageKind::Search, PageKind::Backlinks] {
+            fs::create_dir_all(fixtures_dir.join(kind_dir(*kind)))?;
+        }
+        Ok(RecordingArticleProvider { inner, fixtures_dir })
+    }
+
+    /// Fetch the page of the given `kind` for the `topic`, and record it before returning it.
+    fn record(&self, kind: PageKind, topic: &str) -> Result<Option<String>, ProviderError> {
+        let page = self.inner.fetch_page(kind, topic)?;
+        let written = match page {
+            Some(ref page) => fs::write(fixture_path(&self.fixtures_dir, kind, topic, PAGE_EXTENSION), page),
+            None => fs::write(fixture_path(&self.fixtures_dir, kind, topic, MISSING_EXTENSION), ""),
+        };
+        written.map_err(|err| ProviderError::Io(err.to_string()))?;
+        Ok(page)
+    }
+}
+
+impl ArticleProvider for RecordingArticleProvider {
+    fn get(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, ProviderError> {
+        if topic == "" {
+            return Ok(None);
+        }
+        let include_lists = self.inner.includes_lists();
+        Ok(self.record(PageKind::Article, topic)?
+            .map(|page| HTTPArticleProvider::article_from_page(topic, &page, include_lists)))
+    }
+
+    fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        let page = self.record(PageKind::Search, topic)?.unwrap_or_default();
+        Ok(HTTPArticleProvider::search_results_from_page(&page))
+    }
+
+    fn supports_backlinks(&self) -> bool {
+        true
+    }
+
+    fn backlinks(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        let page = self.record(PageKind::Backlinks, topic)?.unwrap_or_default();
+        Ok(HTTPArticleProvider::backlinks_from_page(&page))
+    }
+}
+
+/// Serves the pages recorded by a `RecordingArticleProvider` in the `fixtures_dir`, parsing them
+/// like `HTTPArticleProvider` does. Asking for a page that was never recorded is an error.
+pub struct ReplayArticleProvider {
+    fixtures_dir: PathBuf,
+    include_lists: bool,
+}
+
+impl ReplayArticleProvider {
+    pub fn new<P: AsRef<Path>>(fixtures_dir: P) -> ReplayArticleProvider {
+        ReplayArticleProvider {
+            fixtures_dir: fixtures_dir.as_ref().to_path_buf(),
+            include_lists: false,
+        }
+    }
+
+    /// Also extract lists, tables and infoboxes as paragraphs; see `HTTPArticleProvider::include_lists`.
+    pub fn include_lists(mut self, include_lists: bool) -> ReplayArticleProvider {
+        self.include_lists = include_lists;
+        self
+    }
+
+    /// Read the recorded page of the given `kind` for the `topic`.
+    fn replay(&self, kind: PageKind, topic: &str) -> Result<Option<String>, ProviderError> {
+        let page_path = fixture_path(&self.fixtures_dir, kind, topic, PAGE_EXTENSION);
+        if page_path.exists() {
+            return fs::read_to_string(&page_path)
+                .map(Some)
+                .map_err(|err| ProviderError::Io(err.to_string()));
+        }
+        if fixture_path(&self.fixtures_dir, kind, topic, MISSING_EXTENSION).exists() {
+            return Ok(None);
+        }
+        Err(ProviderError::Io(format!(
+            "No {:?} page recorded for <{}> in {}",
+            kind,
+            topic,
+            self.fixtures_dir.display()
+        )))
+    }
+}
+
+impl ArticleProvider for ReplayArticleProvider {
+    fn get(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, ProviderError> {
+        if topic == "" {
+            return Ok(None);
+        }
+        Ok(self.replay(PageKind::Article, topic)?
+            .map(|page| HTTPArticleProvider::article_from_page(topic, &page, self.include_lists)))
+    }
+
+    fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        let page = self.replay(PageKind::Search, topic)?.unwrap_or_default();
+        Ok(HTTPArticleProvider::search_results_from_page(&page))
+    }
+
+    fn supports_backlinks(&self) -> bool {
+        true
+    }
+
+    fn backlinks(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        let page = self.replay(PageKind::Backlinks, topic)?.unwrap_or_default();
+        Ok(HTTPArticleProvider::backlinks_from_page(&page))
+    }
+}
+
+fn kind_dir(kind: PageKind) -> &'static str {
+    match kind {
+        PageKind::Article => "article",
+        PageKind::Search => "search",
+        PageKind::Backlinks => "backlinks",
+    }
+}
+
+/// Path of the file recording the page of the given `kind` for the `topic`. Topics sharing a
+/// `TitleKey` share a file; characters which are not safe in file names are percent-encoded.
+fn fixture_path(fixtures_dir: &Path, kind: PageKind, topic: &str, extension: &str) -> PathBuf {
+    let mut name = String::new();
+    for byte in TitleKey::new(topic).as_str().bytes() {
+        match byte {
+            b' ' => name.push('_'),
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b',' | b'(' | b')' | b'\'' => name.push(byte as char),
+            _ => name.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    fixtures_dir.join(kind_dir(kind)).join(format!("{}.{}", name, extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use story_builder::story::render_plain_text;
+    use story_builder::story_builder::StoryBuilder;
+    use std::sync::Arc;
+
+    /// Synthetic (hand-written) pages, in the layout written by `RecordingArticleProvider`.
+    fn recorded_pages() -> ReplayArticleProvider {
+        ReplayArticleProvider::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/http"))
+    }
+
+    #[test]
+    fn fixture_path_escapes_topic() {
+        assert_eq!(
+            fixture_path(Path::new("fixtures"), PageKind::Article, "AC/DC", PAGE_EXTENSION),
+            Path::new("fixtures/article/AC%2FDC.html")
+        );
+        assert_eq!(
+            fixture_path(Path::new("fixtures"), PageKind::Backlinks, "Montréal_(city)", MISSING_EXTENSION),
+            Path::new("fixtures/backlinks/Montr%C3%A9al_(city).missing")
+        );
+    }
+
+    #[test]
+    fn replay_unrecorded_page_is_an_error() {
+        match recorded_pages().get("Never recorded") {
+            Err(ProviderError::Io(_)) => (),
+            Err(err) => panic!("Expected an Io error, got: {}", err),
+            Ok(_) => panic!("Expected an Io error, got Ok."),
+        }
+    }
+
+    #[test]
+    fn replay_article_page() {
+        let article = recorded_pages().get("canada").unwrap().expect("Expected Some, got None.");
+        assert_eq!(article.get_topic(), "Canada");
+        assert_eq!(
+            article.get_paragraphs()[1].topics,
+            vec!["Ottawa", "Toronto", "Montreal", "Vancouver"]
+        );
+    }
+
+    #[test]
+    fn build_story_from_recorded_pages() {
+        let mut story_builder = StoryBuilder::new(Arc::new(recorded_pages()));
+        assert_eq!(
+            story_builder.build_story("Montreal", "Ottawa").map(|story| render_plain_text(&story)),
+            Ok(
+                "-> (Montreal to Canada)\r\nIt is the second-most populous city in Canada.\r\n\
+                 -> (Canada to Ottawa)\r\nIts capital is Ottawa, and its three largest metropolitan areas are Toronto, Montreal, and Vancouver.\r\n"
+                    .to_owned()
+            )
+        );
+    }
+}
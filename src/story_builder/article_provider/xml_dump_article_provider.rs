@@ -0,0 +1,311 @@
+use story_builder::article_provider::*;
+use title::TitleKey;
+use wikitext::{extract_paragraphs, extract_paragraphs_with_lists, is_disambiguation, redirect_target};
+use xml_wiki_parser::{read_backlinks, read_index, read_page_at, read_redirects, WikiPage};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of suggestions returned by `search`.
+const MAX_SEARCH_RESULTS: usize = 10;
+/// Maximum number of redirects followed for a single topic (redirects may point to redirects).
+const MAX_REDIRECTS: usize = 5;
+
+struct XmlDumpArticle {
+    paragraphs: Vec<Paragraph>,
+    topic: String,
+    disambiguation: bool,
+}
+
+impl Article for XmlDumpArticle {
+    fn get_paragraphs(&self) -> &Vec<Paragraph> {
+        &self.paragraphs
+    }
+    fn get_topic(&self) -> &str {
+        &self.topic
+    }
+    fn is_disambiguation(&self) -> bool {
+        self.disambiguation
+    }
+}
+
+/// Provides articles read from a local wikipedia XML dump, using the index
+/// generated by `wiki_xml_indexer` to seek directly to each article.
+pub struct XmlDumpArticleProvider {
+    dump_path: PathBuf,
+    /// Maps the key of a title to the seek position of its <page> and its actual title.
+    index: HashMap<TitleKey, (u64, String)>,
+    /// Maps the key of a redirect title to the title of the article it points to.
+    redirects: HashMap<TitleKey, String>,
+    /// Maps the key of a title to the titles of the articles linking to it.
+    backlinks: HashMap<TitleKey, Vec<String>>,
+}
+
+impl XmlDumpArticleProvider {
+    /// Loads the index found at `index_path` and uses it to read articles
+    /// from the XML dump found at `dump_path`.
+    pub fn new<P: AsRef<Path>, Q: AsRef<Path>>(
+        dump_path: P,
+        index_path: Q,
+    ) -> io::Result<XmlDumpArticleProvider> {
+        let index = read_index(File::open(index_path)?)
+            .into_iter()
+            .map(|(title, page_pos)| (TitleKey::new(&title), (page_pos, title)))
+            .collect();
+        Ok(XmlDumpArticleProvider {
+            dump_path: dump_path.as_ref().to_path_buf(),
+            index,
+            redirects: HashMap::new(),
+            backlinks: HashMap::new(),
+        })
+    }
+
+    /// Loads the redirects table found at `redirects_path` (also generated by `wiki_xml_indexer`)
+    /// so that topics reached through a redirect resolve to the article they point to.
+    pub fn load_redirects<P: AsRef<Path>>(&mut self, redirects_path: P) -> io::Result<()> {
+        self.redirects = read_redirects(File::open(redirects_path)?)
+            .into_iter()
+            .map(|(source, target)| (TitleKey::new(&source), target))
+            .collect();
+        Ok(())
+    }
+
+    /// Loads the backlinks table found at `backlinks_path` (also generated by `wiki_xml_indexer`),
+    /// which enables `backlinks`. Redirects must be loaded first so that links made through a
+    /// redirect are counted as links to the article it points to.
+    pub fn load_backlinks<P: AsRef<Path>>(&mut self, backlinks_path: P) -> io::Result<()> {
+        let mut backlinks: HashMap<TitleKey, Vec<String>> = HashMap::new();
+        for (target, sources) in read_backlinks(File::open(backlinks_path)?) {
+            backlinks
+                .entry(TitleKey::new(&self.resolve_redirects(&target)))
+                .or_insert_with(Vec::new)
+                .extend(sources);
+        }
+        // The same article may link to different aliases of a target; only list it once.
+        for sources in backlinks.values_mut() {
+            sources.sort();
+            sources.dedup();
+        }
+        self.backlinks = backlinks;
+        Ok(())
+    }
+
+    /// Follow the redirects starting at `topic` and return the title they end up on.
+    fn resolve_redirects(&self, topic: &str) -> String {
+        let mut topic = topic.to_owned();
+        for _ in 0..MAX_REDIRECTS {
+            match self.redirects.get(&TitleKey::new(&topic)) {
+                // Drop the section part of the target (Target#Section); we only want the article.
+                Some(target) => topic = target.split('#').next().unwrap_or("").to_owned(),
+                None => break,
+            }
+        }
+        topic
+    }
+
+    fn read_page(&self, topic: &str) -> Result<Option<WikiPage>, ProviderError> {
+        let page_pos = match self.index.get(&TitleKey::new(topic)) {
+            Some(&(page_pos, _)) => page_pos,
+            None => return Ok(None),
+        };
+        let dump = File::open(&self.dump_path).map_err(|err| ProviderError::Io(err.to_string()))?;
+        Ok(read_page_at(dump, page_pos))
+    }
+}
+
+impl ArticleProvider for XmlDumpArticleProvider {
+    fn get(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, ProviderError> {
+        let mut topic = self.resolve_redirects(topic);
+        for _ in 0..MAX_REDIRECTS {
+            let page = match self.read_page(&topic)? {
+                Some(page) => page,
+                None => return Ok(None),
+            };
+            // Indexes generated before redirects were handled also contain the redirect pages.
+            if let Some(target) = redirect_target(&page.text) {
+                topic = self.resolve_redirects(&target);
+                continue;
+            }
+            // The candidates of disambiguation pages are listed; keep them.
+            let disambiguation = is_disambiguation(&page.text);
+            let mut paragraphs = if disambiguation {
+                extract_paragraphs_with_lists(&page.text)
+            } else {
+                extract_paragraphs(&page.text)
+            };
+            // Report the canonical title of linked articles rather than the alias used in the link.
+            for paragraph in paragraphs.iter_mut() {
+                for linked_topic in paragraph.topics.iter_mut() {
+                    *linked_topic = self.resolve_redirects(linked_topic);
+                }
+            }
+            return Ok(Some(Box::new(XmlDumpArticle {
+                paragraphs,
+                topic: page.title,
+                disambiguation,
+            })));
+        }
+        Ok(None)
+    }
+
+    fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        let topic = topic.to_lowercase();
+        let mut results: Vec<String> = self.index
+            .values()
+            .map(|(_, title)| title)
+            .filter(|title| title.to_lowercase().contains(&topic))
+            .cloned()
+            .collect();
+        results.sort();
+        results.truncate(MAX_SEARCH_RESULTS);
+        Ok(results)
+    }
+
+    fn supports_backlinks(&self) -> bool {
+        !self.backlinks.is_empty()
+    }
+
+    fn backlinks(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        Ok(self.backlinks
+            .get(&TitleKey::new(&self.resolve_redirects(topic)))
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+
+    static DUMP: &'static str = "<mediawiki>\n  \
+        <page>\n    \
+        <title>Cat</title>\n    <ns>0</ns>\n    <revision>\n      \
+        <text xml:space=\"preserve\">The '''cat''' is a small [[Carnivore|carnivorous]] [[mammal]].\n\n\
+        [[File:Cat.jpg|thumb|A cat]]\n\n\
+        == History ==\n\n\
+        Cats were revered in [[Egypt (ancient)|Egypt]].</text>\n    \
+        </revision>\n  </page>\n  \
+        <page>\n    \
+        <title>Kitty</title>\n    <ns>0</ns>\n    <revision>\n      \
+        <text xml:space=\"preserve\">#REDIRECT [[Cat]]</text>\n    \
+        </revision>\n  </page>\n  \
+        <page>\n    \
+        <title>Ancient Egypt</title>\n    <ns>0</ns>\n    <revision>\n      \
+        <text xml:space=\"preserve\" />\n    \
+        </revision>\n  </page>\n  \
+        <page>\n    \
+        <title>Cat (disambiguation)</title>\n    <ns>0</ns>\n    <revision>\n      \
+        <text xml:space=\"preserve\">'''Cat''' may refer to:\n\
+        * [[Cat]], a small mammal\n\
+        * [[Cat (Unix)|cat]], a command\n\
+        {{Disambiguation}}</text>\n    \
+        </revision>\n  </page>\n\
+        </mediawiki>";
+
+    /// Write the test dump and an index pointing to its pages in a temporary directory.
+    fn build_provider(name: &str) -> XmlDumpArticleProvider {
+        let dir = env::temp_dir().join(format!("wikistory_{}", name));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("dump.xml"), DUMP).unwrap();
+        let mut index = File::create(dir.join("index.csv")).unwrap();
+        // Kitty is indexed like an article, as older indexes did for redirect pages.
+        for title in &["Cat", "Kitty", "Ancient Egypt", "Cat (disambiguation)"] {
+            let page_pos = DUMP.find(&format!("<page>\n    <title>{}<", title)).unwrap();
+            write!(index, "{},{}\r\n", page_pos, title).unwrap();
+        }
+        fs::write(dir.join("redirects.tsv"), "Egypt (ancient)\tAncient Egypt\r\n").unwrap();
+        let mut provider =
+            XmlDumpArticleProvider::new(dir.join("dump.xml"), dir.join("index.csv")).unwrap();
+        provider.load_redirects(dir.join("redirects.tsv")).unwrap();
+        fs::write(
+            dir.join("backlinks.tsv"),
+            "Egypt (ancient)\tCat\r\nAncient Egypt\tCat\r\nAncient Egypt\tPharaoh\r\n",
+        ).unwrap();
+        provider.load_backlinks(dir.join("backlinks.tsv")).unwrap();
+        provider
+    }
+
+    #[test]
+    fn get_reads_paragraphs_and_topics_from_dump() {
+        let provider = build_provider("get_reads_paragraphs");
+        let article = provider.get("cat").unwrap().expect("Expected Some, got None.");
+        assert_eq!(article.get_topic(), "Cat");
+        let paragraphs = article.get_paragraphs();
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(
+            paragraphs[0].text,
+            "The cat is a small carnivorous mammal."
+        );
+        assert_eq!(paragraphs[0].topics, vec!["Carnivore", "mammal"]);
+        assert_eq!(paragraphs[1].topics, vec!["Ancient Egypt"]);
+    }
+
+    #[test]
+    fn get_flags_disambiguation_pages() {
+        let provider = build_provider("get_disambiguation");
+        let article = provider.get("Cat (disambiguation)").unwrap().expect("Expected Some, got None.");
+        assert!(article.is_disambiguation());
+        let topics: Vec<&str> = article
+            .get_paragraphs()
+            .iter()
+            .flat_map(|paragraph| paragraph.topics.iter().map(|topic| topic.as_str()))
+            .collect();
+        assert_eq!(topics, vec!["Cat", "Cat (Unix)"]);
+        assert!(!provider.get("Cat").unwrap().unwrap().is_disambiguation());
+    }
+
+    #[test]
+    fn get_empty_article_returns_no_paragraphs() {
+        let provider = build_provider("get_empty_article");
+        let article = provider.get("Ancient Egypt").unwrap().expect("Expected Some, got None.");
+        assert!(article.get_paragraphs().is_empty());
+    }
+
+    #[test]
+    fn get_follows_redirects() {
+        let provider = build_provider("get_follows_redirects");
+        let article = provider.get("egypt (ancient)").unwrap().expect("Expected Some, got None.");
+        assert_eq!(article.get_topic(), "Ancient Egypt");
+        let article = provider.get("Kitty").unwrap().expect("Expected Some, got None.");
+        assert_eq!(article.get_topic(), "Cat");
+    }
+
+    #[test]
+    fn get_from_non_indexed_returns_none() {
+        let provider = build_provider("get_non_indexed");
+        match provider.get("Dog") {
+            Ok(None) => (),
+            _ => panic!("Expected None, got Some."),
+        }
+    }
+
+    #[test]
+    fn backlinks_are_resolved_through_redirects() {
+        let provider = build_provider("backlinks");
+        assert!(provider.supports_backlinks());
+        assert_eq!(provider.backlinks("Ancient Egypt").unwrap(), vec!["Cat", "Pharaoh"]);
+        assert_eq!(provider.backlinks("egypt (ancient)").unwrap(), vec!["Cat", "Pharaoh"]);
+        assert!(provider.backlinks("Cat").unwrap().is_empty());
+    }
+
+    #[test]
+    fn get_from_missing_dump_returns_error() {
+        let mut provider = build_provider("get_missing_dump");
+        provider.dump_path = env::temp_dir().join("wikistory_get_missing_dump").join("missing.xml");
+        match provider.get("Cat") {
+            Err(ProviderError::Io(_)) => (),
+            _ => panic!("Expected an Io error."),
+        }
+    }
+
+    #[test]
+    fn search_returns_titles_containing_topic() {
+        let provider = build_provider("search");
+        assert_eq!(provider.search("egypt").unwrap(), vec!["Ancient Egypt"]);
+        assert!(provider.search("dog").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+use title::TitleKey;
+
+/// A rule keeping the stories of `StoryBuilder` away from some articles. Some articles (countries,
+/// years, lists) link to and from so many others that most stories would go through them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExclusionRule {
+    /// The article of this topic.
+    Topic(TitleKey),
+    /// The articles whose title starts with this prefix, such as "List of ".
+    Prefix(String),
+    /// The articles about a year, a decade, a century or a millennium ("1984", "1980s", "44 BC",
+    /// "19th century", "1984 in film").
+    Years,
+    /// The articles about a country, according to their title on the English Wikipedia.
+    Countries,
+}
+
+impl ExclusionRule {
+    /// Reads a rule as written by users: `<years>` and `<countries>` (titles may not contain `<`),
+    /// a prefix ending with `*` (`List of *`) or the title of an article.
+    pub fn parse(rule: &str) -> ExclusionRule {
+        match rule.trim() {
+            "<years>" => ExclusionRule::Years,
+            "<countries>" => ExclusionRule::Countries,
+            rule if rule.len() > 1 && rule.ends_with('*') => {
+                let prefix = &rule[..rule.len() - 1];
+                let mut key = TitleKey::new(prefix).as_str().to_owned();
+                // Normalizing the prefix removes the space ending "List of ".
+                if prefix.ends_with(|c: char| c.is_whitespace() || c == '_') {
+                    key.push(' ');
+                }
+                ExclusionRule::Prefix(key)
+            }
+            rule => ExclusionRule::Topic(TitleKey::new(rule)),
+        }
+    }
+
+    /// Returns whether the rule excludes the article with this title.
+    pub fn excludes(&self, title: &TitleKey) -> bool {
+        match *self {
+            ExclusionRule::Topic(ref topic) => topic == title,
+            ExclusionRule::Prefix(ref prefix) => title.as_str().starts_with(prefix.as_str()),
+            ExclusionRule::Years => is_year(title.as_str()),
+            ExclusionRule::Countries => COUNTRIES.binary_search(&title.as_str()).is_ok(),
+        }
+    }
+}
+
+fn is_year(title: &str) -> bool {
+    // "1984 in film" and the like are about a year too.
+    let mut title = title.split(" in ").next().unwrap_or("");
+    if title.starts_with("AD ") {
+        title = &title[3..];
+    }
+    for era in &[" BC", " BCE", " AD", " CE"] {
+        if title.ends_with(era) {
+            title = &title[..title.len() - era.len()];
+            break;
+        }
+    }
+    let digits = title.find(|c: char| !c.is_ascii_digit()).unwrap_or(title.len());
+    let (number, suffix) = title.split_at(digits);
+    if number.is_empty() || number.len() > 4 {
+        return false;
+    }
+    match suffix {
+        "" => true,
+        "s" => number.ends_with('0'),
+        _ => ["st", "nd", "rd", "th"].iter().any(|ordinal| {
+            suffix.starts_with(ordinal) && (&suffix[2..] == " century" || &suffix[2..] == " millennium")
+        }),
+    }
+}
+
+/// Titles of the sovereign states (and of the countries of the United Kingdom), sorted for
+/// binary searches.
+const COUNTRIES: &'static [&'static str] = &[
+    "Afghanistan", "Albania", "Algeria", "Andorra", "Angola", "Antigua and Barbuda", "Argentina", "Armenia",
+    "Australia", "Austria", "Azerbaijan", "Bahrain", "Bangladesh", "Barbados", "Belarus", "Belgium", "Belize",
+    "Benin", "Bhutan", "Bolivia", "Bosnia and Herzegovina", "Botswana", "Brazil", "Brunei", "Bulgaria",
+    "Burkina Faso", "Burundi", "Cambodia", "Cameroon", "Canada", "Cape Verde", "Central African Republic",
+    "Chad", "Chile", "China", "Colombia", "Comoros", "Costa Rica", "Croatia", "Cuba", "Cyprus",
+    "Czech Republic", "Democratic Republic of the Congo", "Denmark", "Djibouti", "Dominica",
+    "Dominican Republic", "East Timor", "Ecuador", "Egypt", "El Salvador", "England", "Equatorial Guinea",
+    "Eritrea", "Estonia", "Eswatini", "Ethiopia", "Federated States of Micronesia", "Fiji", "Finland",
+    "France", "Gabon", "Georgia (country)", "Germany", "Ghana", "Greece", "Grenada", "Guatemala", "Guinea",
+    "Guinea-Bissau", "Guyana", "Haiti", "Honduras", "Hungary", "Iceland", "India", "Indonesia", "Iran",
+    "Iraq", "Israel", "Italy", "Ivory Coast", "Jamaica", "Japan", "Jordan", "Kazakhstan", "Kenya", "Kiribati",
+    "Kosovo", "Kuwait", "Kyrgyzstan", "Laos", "Latvia", "Lebanon", "Lesotho", "Liberia", "Libya",
+    "Liechtenstein", "Lithuania", "Luxembourg", "Madagascar", "Malawi", "Malaysia", "Maldives", "Mali",
+    "Malta", "Marshall Islands", "Mauritania", "Mauritius", "Mexico", "Moldova", "Monaco", "Mongolia",
+    "Montenegro", "Morocco", "Mozambique", "Myanmar", "Namibia", "Nauru", "Nepal", "Netherlands",
+    "New Zealand", "Nicaragua", "Niger", "Nigeria", "North Korea", "North Macedonia", "Northern Ireland",
+    "Norway", "Oman", "Pakistan", "Palau", "Panama", "Papua New Guinea", "Paraguay", "Peru", "Philippines",
+    "Poland", "Portugal", "Qatar", "Republic of Ireland", "Republic of the Congo", "Romania", "Russia",
+    "Rwanda", "Saint Kitts and Nevis", "Saint Lucia", "Saint Vincent and the Grenadines", "Samoa",
+    "San Marino", "Saudi Arabia", "Scotland", "Senegal", "Serbia", "Seychelles", "Sierra Leone", "Singapore",
+    "Slovakia", "Slovenia", "Solomon Islands", "Somalia", "South Africa", "South Korea", "South Sudan",
+    "Spain", "Sri Lanka", "State of Palestine", "Sudan", "Suriname", "Sweden", "Switzerland", "Syria",
+    "São Tomé and Príncipe", "Taiwan", "Tajikistan", "Tanzania", "Thailand", "The Bahamas", "The Gambia",
+    "Togo", "Tonga", "Trinidad and Tobago", "Tunisia", "Turkey", "Turkmenistan", "Tuvalu", "Uganda",
+    "Ukraine", "United Arab Emirates", "United Kingdom", "United States", "Uruguay", "Uzbekistan", "Vanuatu",
+    "Vatican City", "Venezuela", "Vietnam", "Wales", "Yemen", "Zambia", "Zimbabwe",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn excludes(rule: &str, title: &str) -> bool {
+        ExclusionRule::parse(rule).excludes(&TitleKey::new(title))
+    }
+
+    #[test]
+    fn parse_reads_each_kind_of_rule() {
+        assert_eq!(ExclusionRule::parse("<years>"), ExclusionRule::Years);
+        assert_eq!(ExclusionRule::parse(" <countries> "), ExclusionRule::Countries);
+        assert_eq!(ExclusionRule::parse("list of *"), ExclusionRule::Prefix("List of ".to_owned()));
+        assert_eq!(ExclusionRule::parse("United_States"), ExclusionRule::Topic(TitleKey::new("United States")));
+    }
+
+    #[test]
+    fn topic_rule_excludes_the_same_title() {
+        assert!(excludes("united_States", "United States"));
+        assert!(!excludes("United States", "United States Army"));
+    }
+
+    #[test]
+    fn prefix_rule_excludes_titles_starting_with_it() {
+        assert!(excludes("List of *", "List of cities in Canada"));
+        assert!(excludes("list_of_*", "List of cities in Canada"));
+        assert!(!excludes("List of *", "List offerings"));
+        assert!(!excludes("List of *", "Lists of cities"));
+    }
+
+    #[test]
+    fn years_rule_excludes_years_decades_and_centuries() {
+        for title in &["1984", "42", "44 BC", "AD 79", "1980s", "19th century", "2nd millennium BC", "1984 in film"] {
+            assert!(excludes("<years>", title), "{} is not excluded", title);
+        }
+        for title in &["Apollo 11", "1984 (novel)", "24 (TV series)", "10000", "1984s", "3rd Street"] {
+            assert!(!excludes("<years>", title), "{} is excluded", title);
+        }
+    }
+
+    #[test]
+    fn countries_rule_excludes_countries() {
+        assert!(excludes("<countries>", "United States"));
+        assert!(excludes("<countries>", "São Tomé and Príncipe"));
+        assert!(!excludes("<countries>", "Montreal"));
+        assert!(!excludes("<countries>", "Georgia"));
+    }
+
+    #[test]
+    fn countries_are_sorted() {
+        let mut sorted = COUNTRIES.to_vec();
+        sorted.sort();
+        assert_eq!(COUNTRIES, &sorted[..]);
+    }
+}
@@ -0,0 +1,250 @@
+use serde_json::Value;
+use std::time::Duration;
+use story_builder::story_builder::{StoryError, TopicRole};
+
+/// A story built by `StoryBuilder`: the list of hops going from the start topic to the end topic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Story {
+    pub start_topic: String,
+    pub end_topic: String,
+    pub hops: Vec<Hop>,
+    pub stats: SearchStats,
+}
+
+/// One step of a story: the `paragraph` of the `from` article that links to the `to` article.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hop {
+    /// Position of the hop in the story, starting at 0.
+    pub index: usize,
+    pub from: String,
+    pub to: String,
+    pub paragraph: String,
+    /// Headings of the section of the `from` article holding the paragraph, outermost first;
+    /// empty if the paragraph is in the lead section.
+    pub section: Vec<String>,
+}
+
+/// Statistics about the search that built a story.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchStats {
+    /// Number of levels of articles the search went through.
+    pub depth: u8,
+    /// Number of articles fetched from the article provider.
+    pub articles_fetched: usize,
+    pub elapsed: Duration,
+}
+
+/// Renders the `story` as plain text; each hop is rendered as a `-> (from to to)` line,
+/// naming the section of the paragraph unless it is in the lead, followed by its paragraph.
+pub fn render_plain_text(story: &Story) -> String {
+    story
+        .hops
+        .iter()
+        .map(|hop| {
+            if hop.section.is_empty() {
+                format!("-> ({} to {})\r\n{}\r\n", hop.from, hop.to, hop.paragraph)
+            } else {
+                format!(
+                    "-> ({} to {}, from the {} section)\r\n{}\r\n",
+                    hop.from,
+                    hop.to,
+                    hop.section.join(" > "),
+                    hop.paragraph
+                )
+            }
+        })
+        .collect()
+}
+
+/// Renders the `story` as a JSON document, for other programs to consume.
+pub fn render_json(story: &Story) -> String {
+    story_to_json(story).to_string()
+}
+
+/// Renders the `stories` returned by `build_stories` as a JSON document holding the list of
+/// their documents (as rendered by `render_json`) under `stories`.
+pub fn render_json_stories(stories: &[Story]) -> String {
+    let stories: Vec<Value> = stories.iter().map(story_to_json).collect();
+    json!({ "stories": stories }).to_string()
+}
+
+fn story_to_json(story: &Story) -> Value {
+    let hops: Vec<Value> = story
+        .hops
+        .iter()
+        .map(|hop| {
+            json!({
+                "index": hop.index,
+                "from": hop.from,
+                "to": hop.to,
+                "paragraph": hop.paragraph,
+                "section": hop.section,
+            })
+        })
+        .collect();
+    json!({
+        "start_topic": story.start_topic,
+        "end_topic": story.end_topic,
+        "hops": hops,
+        "depth": story.stats.depth,
+        "articles_fetched": story.stats.articles_fetched,
+        "elapsed_ms": duration_as_millis(story.stats.elapsed),
+    })
+}
+
+/// Renders the `err` returned by `build_story` as a JSON document. The `kind` of the error
+/// can be matched by other programs; `suggestions` is empty unless an article was not found
+/// or a topic is ambiguous.
+pub fn render_json_error(err: &StoryError) -> String {
+    let kind = match *err {
+        StoryError::EmptyTopic(TopicRole::Start) => "empty_start_topic",
+        StoryError::EmptyTopic(TopicRole::End) => "empty_end_topic",
+        StoryError::EmptyTopic(TopicRole::Waypoint) => "empty_waypoint_topic",
+        StoryError::SameTopics => "same_topics",
+        StoryError::ArticleNotFound { .. } => "article_not_found",
+        StoryError::AmbiguousTopic { .. } => "ambiguous_topic",
+        StoryError::DepthExhausted { .. } => "depth_exhausted",
+        StoryError::ArticleBudgetExhausted { .. } => "article_budget_exhausted",
+        StoryError::DeadlineExceeded { .. } => "deadline_exceeded",
+        StoryError::FrontierExhausted { .. } => "frontier_exhausted",
+        StoryError::Provider(_) => "provider",
+    };
+    let suggestions = match *err {
+        StoryError::ArticleNotFound { ref suggestions, .. } => suggestions.clone(),
+        StoryError::AmbiguousTopic { ref candidates, .. } => candidates.clone(),
+        _ => vec![],
+    };
+    json!({
+        "error": {
+            "kind": kind,
+            "message": err.to_string(),
+            "suggestions": suggestions,
+        }
+    }).to_string()
+}
+
+fn duration_as_millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + u64::from(duration.subsec_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_plain_text_of_empty_story() {
+        let story = Story {
+            start_topic: "start".to_owned(),
+            end_topic: "end".to_owned(),
+            hops: vec![],
+            stats: SearchStats {
+                depth: 0,
+                articles_fetched: 0,
+                elapsed: Duration::from_secs(0),
+            },
+        };
+        assert_eq!(render_plain_text(&story), "");
+    }
+
+    #[test]
+    fn render_plain_text_of_each_hop() {
+        let story = Story {
+            start_topic: "Cat".to_owned(),
+            end_topic: "Montreal".to_owned(),
+            hops: vec![
+                Hop {
+                    index: 0,
+                    from: "Cat".to_owned(),
+                    to: "Canada".to_owned(),
+                    paragraph: "Cats live in Canada.".to_owned(),
+                    section: vec![],
+                },
+                Hop {
+                    index: 1,
+                    from: "Canada".to_owned(),
+                    to: "Montreal".to_owned(),
+                    paragraph: "Montreal is in Canada.".to_owned(),
+                    section: vec!["Cities".to_owned(), "Quebec".to_owned()],
+                },
+            ],
+            stats: SearchStats {
+                depth: 2,
+                articles_fetched: 12,
+                elapsed: Duration::from_millis(1500),
+            },
+        };
+        assert_eq!(
+            render_plain_text(&story),
+            "-> (Cat to Canada)\r\nCats live in Canada.\r\n\
+             -> (Canada to Montreal, from the Cities > Quebec section)\r\nMontreal is in Canada.\r\n"
+        );
+    }
+
+    #[test]
+    fn render_json_of_story() {
+        let story = Story {
+            start_topic: "Cat".to_owned(),
+            end_topic: "Canada".to_owned(),
+            hops: vec![
+                Hop {
+                    index: 0,
+                    from: "Cat".to_owned(),
+                    to: "Canada".to_owned(),
+                    paragraph: "Cats live in \"Canada\".".to_owned(),
+                    section: vec!["Habitat".to_owned()],
+                },
+            ],
+            stats: SearchStats {
+                depth: 1,
+                articles_fetched: 2,
+                elapsed: Duration::from_millis(1500),
+            },
+        };
+        let json: Value = ::serde_json::from_str(&render_json(&story)).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "start_topic": "Cat",
+                "end_topic": "Canada",
+                "hops": [{
+                    "index": 0,
+                    "from": "Cat",
+                    "to": "Canada",
+                    "paragraph": "Cats live in \"Canada\".",
+                    "section": ["Habitat"],
+                }],
+                "depth": 1,
+                "articles_fetched": 2,
+                "elapsed_ms": 1500,
+            })
+        );
+
+        let json: Value = ::serde_json::from_str(&render_json_stories(&[story.clone(), story])).unwrap();
+        assert_eq!(json["stories"].as_array().map(|stories| stories.len()), Some(2));
+        assert_eq!(json["stories"][1]["hops"][0]["to"], "Canada");
+    }
+
+    #[test]
+    fn render_json_error_with_suggestions() {
+        let err = StoryError::ArticleNotFound {
+            topic: "cat".to_owned(),
+            suggestions: vec!["Cat".to_owned(), "Catalonia".to_owned()],
+        };
+        let json: Value = ::serde_json::from_str(&render_json_error(&err)).unwrap();
+        assert_eq!(json["error"]["kind"], "article_not_found");
+        assert_eq!(json["error"]["message"], err.to_string());
+        assert_eq!(json["error"]["suggestions"], json!(["Cat", "Catalonia"]));
+
+        let err = StoryError::AmbiguousTopic {
+            topic: "Mercury (disambiguation)".to_owned(),
+            candidates: vec!["Mercury (planet)".to_owned(), "Mercury (element)".to_owned()],
+        };
+        let json: Value = ::serde_json::from_str(&render_json_error(&err)).unwrap();
+        assert_eq!(json["error"]["kind"], "ambiguous_topic");
+        assert_eq!(json["error"]["suggestions"], json!(["Mercury (planet)", "Mercury (element)"]));
+
+        let json: Value = ::serde_json::from_str(&render_json_error(&StoryError::SameTopics)).unwrap();
+        assert_eq!(json["error"]["kind"], "same_topics");
+        assert_eq!(json["error"]["suggestions"], json!([]));
+    }
+}
@@ -1,54 +1,439 @@
 use story_builder::article_provider::*;
-use std::borrow::Borrow;
+use story_builder::exclusion::ExclusionRule;
+use story_builder::story::{Hop, SearchStats, Story};
+use title::TitleKey;
 use std::ops::Deref;
 use std::sync::{Arc, Mutex};
-use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
 use rayon::prelude::*;
 
+/// The topics given to `build_story` (and `build_story_via`).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TopicRole {
+    Start,
+    End,
+    Waypoint,
+}
+
+/// The reasons `build_story` may fail to build a story.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoryError {
+    /// The start or end topic is empty.
+    EmptyTopic(TopicRole),
+    /// The start and end topics are the same; there is no story to build.
+    SameTopics,
+    /// There is no article for the `topic`; the `suggestions` are topics with a similar name.
+    ArticleNotFound {
+        topic: String,
+        suggestions: Vec<String>,
+    },
+    /// The `topic` leads to a disambiguation page; the `candidates` are the articles it may refer to.
+    AmbiguousTopic {
+        topic: String,
+        candidates: Vec<String>,
+    },
+    /// No story of at most `depth` hops could be found between both topics.
+    DepthExhausted { depth: u8, topic: String },
+    /// `max_articles` articles were fetched without finding the `topic`.
+    ArticleBudgetExhausted { max_articles: usize, topic: String },
+    /// The search ran for longer than the `timeout` without finding the `topic`.
+    DeadlineExceeded { timeout: Duration, topic: String },
+    /// A level of the search held more than `max_frontier` articles without reaching the `topic`.
+    FrontierExhausted { max_frontier: usize, topic: String },
+    /// The article provider failed to provide an article.
+    Provider(ProviderError),
+}
+
+impl fmt::Display for StoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StoryError::EmptyTopic(TopicRole::Start) => write!(f, "Missing start topic."),
+            StoryError::EmptyTopic(TopicRole::End) => write!(f, "Missing end topic."),
+            StoryError::EmptyTopic(TopicRole::Waypoint) => write!(f, "Missing waypoint topic."),
+            StoryError::SameTopics => write!(f, "No story to build; same start and end topics."),
+            StoryError::ArticleNotFound {
+                ref topic,
+                ref suggestions,
+            } => write!(
+                f,
+                "{}",
+                StoryBuilder::build_suggestions_msg(
+                    &format!(
+                        "Cannot find wikipedia article for <{}>, try one of the following suggestions:",
+                        topic
+                    ),
+                    suggestions
+                )
+            ),
+            StoryError::AmbiguousTopic {
+                ref topic,
+                ref candidates,
+            } => write!(
+                f,
+                "{}",
+                StoryBuilder::build_suggestions_msg(
+                    &format!("<{}> may refer to several articles, try one of the following:", topic),
+                    candidates
+                )
+            ),
+            StoryError::DepthExhausted { depth, ref topic } => write!(
+                f,
+                "Reached depth of <{}> without finding <{}>. Stopping search.",
+                depth,
+                topic
+            ),
+            StoryError::ArticleBudgetExhausted {
+                max_articles,
+                ref topic,
+            } => write!(
+                f,
+                "Fetched <{}> articles without finding <{}>. Stopping search.",
+                max_articles,
+                topic
+            ),
+            StoryError::DeadlineExceeded { timeout, ref topic } => write!(
+                f,
+                "Searched for <{}> ms without finding <{}>. Stopping search.",
+                timeout.as_secs() * 1000 + u64::from(timeout.subsec_millis()),
+                topic
+            ),
+            StoryError::FrontierExhausted {
+                max_frontier,
+                ref topic,
+            } => write!(
+                f,
+                "Reached more than <{}> articles on a single level without finding <{}>. Stopping search.",
+                max_frontier,
+                topic
+            ),
+            StoryError::Provider(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for StoryError {
+    fn description(&self) -> &str {
+        "unable to build story"
+    }
+}
+
+impl From<ProviderError> for StoryError {
+    fn from(err: ProviderError) -> StoryError {
+        StoryError::Provider(err)
+    }
+}
+
+/// The budgets which stop a search early; `build_story` reports the one that ran out with
+/// the matching `StoryError`.
+enum Budget {
+    Depth,
+    Articles,
+    Deadline,
+    Frontier,
+}
+
+/// Why a search stopped before finding a story.
+enum SearchError {
+    Exhausted(Budget),
+    Provider(ProviderError),
+}
+
+impl From<ProviderError> for SearchError {
+    fn from(err: ProviderError) -> SearchError {
+        SearchError::Provider(err)
+    }
+}
+
+/// Limits on how far `StoryBuilder` searches before giving up, built with its builder methods;
+/// e.g. `StoryBuilderConfig::new().max_depth(4).timeout(Duration::from_secs(30))`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoryBuilderConfig {
+    /// Maximum number of hops of a story.
+    pub max_depth: u8,
+    /// Maximum number of articles fetched from the article provider for a single story.
+    pub max_articles: Option<usize>,
+    /// Maximum time spent building a single story.
+    pub timeout: Option<Duration>,
+    /// Maximum number of articles (or backlinks) in a single level of the search.
+    pub max_frontier: Option<usize>,
+    /// Rules for the articles stories must not go through. The start and end topics are never excluded.
+    pub avoid: Vec<ExclusionRule>,
+}
+
+impl StoryBuilderConfig {
+    /// A config with a depth of 5, no other limits and no excluded articles.
+    pub fn new() -> StoryBuilderConfig {
+        StoryBuilderConfig {
+            max_depth: 5,
+            max_articles: None,
+            timeout: None,
+            max_frontier: None,
+            avoid: vec![],
+        }
+    }
+    pub fn max_depth(mut self, max_depth: u8) -> StoryBuilderConfig {
+        self.max_depth = max_depth;
+        self
+    }
+    pub fn max_articles(mut self, max_articles: usize) -> StoryBuilderConfig {
+        self.max_articles = Some(max_articles);
+        self
+    }
+    pub fn timeout(mut self, timeout: Duration) -> StoryBuilderConfig {
+        self.timeout = Some(timeout);
+        self
+    }
+    pub fn max_frontier(mut self, max_frontier: usize) -> StoryBuilderConfig {
+        self.max_frontier = Some(max_frontier);
+        self
+    }
+    pub fn avoid(mut self, rule: ExclusionRule) -> StoryBuilderConfig {
+        self.avoid.push(rule);
+        self
+    }
+}
+
+impl Default for StoryBuilderConfig {
+    fn default() -> StoryBuilderConfig {
+        StoryBuilderConfig::new()
+    }
+}
+
+/// The ways `StoryBuilder` can search for the shortest story between two topics.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SearchStrategy {
+    /// Follow the links of each article, level by level, starting from the start article.
+    Forward,
+    /// Follow links from the start article and backlinks from the end article until both meet.
+    /// Falls back to `Forward` when the article provider does not support backlinks.
+    Bidirectional,
+}
+
 pub struct StoryBuilder {
     article_provider: Arc<ThreadedAP>,
-    max_depth: u8,
-    visited_nodes: HashSet<String>,
+    config: StoryBuilderConfig,
+    /// When the current story must be abandoned, according to the `timeout` of the config.
+    deadline: Option<Instant>,
+    /// Keys of the topics already fetched (or being fetched) for the current story.
+    visited_nodes: Mutex<HashSet<TitleKey>>,
+    /// Keys of the topics the previous searches of a `build_story_via` go through, which the
+    /// next ones must not go through again.
+    taken_topics: HashSet<TitleKey>,
+    articles_fetched: AtomicUsize,
+    strategy: SearchStrategy,
 }
 
 impl StoryBuilder {
     pub fn new(article_provider: Arc<ThreadedAP>) -> StoryBuilder {
+        StoryBuilder::with_config(article_provider, StoryBuilderConfig::new())
+    }
+
+    pub fn with_config(article_provider: Arc<ThreadedAP>, config: StoryBuilderConfig) -> StoryBuilder {
         StoryBuilder {
             article_provider,
-            max_depth: 5, // default value for now
-            visited_nodes: HashSet::new(),
+            config,
+            deadline: None,
+            visited_nodes: Mutex::new(HashSet::new()),
+            taken_topics: HashSet::new(),
+            articles_fetched: AtomicUsize::new(0),
+            strategy: SearchStrategy::Bidirectional,
         }
     }
 
-    pub fn build_story(&mut self, start_topic: &str, end_topic: &str) -> Result<String, String> {
-        let start_topic = start_topic.to_lowercase();
-        let end_topic = end_topic.to_lowercase();
+    /// Builds the shortest story going from the `start_topic` to the `end_topic`. Although articles
+    /// are fetched in parallel, the search is reproducible: the same articles always give the same story.
+    pub fn build_story(&mut self, start_topic: &str, end_topic: &str) -> Result<Story, StoryError> {
+        let mut stories = self.build_stories(start_topic, end_topic, 1)?;
+        Ok(stories.remove(0))
+    }
+
+    /// Builds up to `k` distinct stories between both topics: the shortest ones, then longer ones
+    /// if there are fewer than `k` of those (stories going through an article that already links to
+    /// the end topic are left out, as they are only detours). Shorter stories come first; stories of
+    /// the same length are ordered by the topics they go through. Unless it fails, at least one story
+    /// is returned, even if a limit of the config stops the search before `k` are found.
+    pub fn build_stories(&mut self, start_topic: &str, end_topic: &str, k: usize) -> Result<Vec<Story>, StoryError> {
+        let start_time = Instant::now();
+        self.start_search(start_time);
+        self.search_stories(start_topic, end_topic, k, start_time)
+    }
+
+    /// Resets the limits of the config for a new story, started at `start_time`.
+    fn start_search(&mut self, start_time: Instant) {
+        self.taken_topics.clear();
+        self.articles_fetched.store(0, Ordering::SeqCst);
+        self.deadline = self.config.timeout.map(|timeout| start_time + timeout);
+    }
+
+    /// Searches the stories of `build_stories`, within what is left of the limits of the config,
+    /// and without going through the `taken_topics`.
+    fn search_stories(
+        &mut self,
+        start_topic: &str,
+        end_topic: &str,
+        k: usize,
+        start_time: Instant,
+    ) -> Result<Vec<Story>, StoryError> {
+        let start_key = TitleKey::new(start_topic);
+        let end_key = TitleKey::new(end_topic);
         // If one of the topics is an empty string, do not try to make a story out of it.
-        if start_topic == "" {
-            return Err("Missing start topic.".to_owned());
+        if start_key.is_empty() {
+            return Err(StoryError::EmptyTopic(TopicRole::Start));
         }
-        if end_topic == "" {
-            return Err("Missing end topic.".to_owned());
+        if end_key.is_empty() {
+            return Err(StoryError::EmptyTopic(TopicRole::End));
         }
 
         // If both topics are the same,
         // there is no point in trying to figure out the story.
-        if start_topic == end_topic {
-            return Err("No story to build; same start and end topics.".to_owned());
+        if start_key == end_key {
+            return Err(StoryError::SameTopics);
+        }
+        if k == 0 {
+            return Ok(vec![]);
+        }
+        // Each search starts from scratch, except for the topics taken by the previous ones.
+        {
+            let mut visited_nodes = self.visited_nodes.lock().unwrap();
+            visited_nodes.clear();
+            visited_nodes.extend(self.taken_topics.iter().cloned());
         }
 
         // Load the first article
-        let start_article = self.article_provider
-            .get(&start_topic)
-            .ok_or_else(|| self.build_suggestions_msg(&start_topic))?;
-        // Insert it in the node cache
-        self.visited_nodes.insert(start_topic);
+        let start_article = match self.fetch(start_topic).map_err(|err| self.story_error(err, end_topic))? {
+            Some(article) => article,
+            None => return Err(self.article_not_found(start_topic)),
+        };
+        if start_article.is_disambiguation() {
+            return Err(StoryBuilder::ambiguous_topic(&*start_article));
+        }
         // Load the end article, so an error is returned if the article does not exist (so we don't search forever for
         // a topic that does not exist).
-        let end_article = self.article_provider
-            .get(&end_topic)
-            .ok_or_else(|| self.build_suggestions_msg(&end_topic))?;
+        let end_article = match self.fetch(end_topic).map_err(|err| self.story_error(err, end_topic))? {
+            Some(article) => article,
+            None => return Err(self.article_not_found(end_topic)),
+        };
+        if end_article.is_disambiguation() {
+            return Err(StoryBuilder::ambiguous_topic(&*end_article));
+        }
+        // The end topic may be a redirect; links to it may use either the topic asked for or the
+        // canonical title of the article, and the story reports the canonical title.
+        let end_topics = vec![end_key, TitleKey::new(end_article.get_topic())];
+        // Insert both in the node cache, so they are never fetched again.
+        {
+            let mut visited_nodes = self.visited_nodes.lock().unwrap();
+            visited_nodes.insert(start_key);
+            visited_nodes.insert(TitleKey::new(start_article.get_topic()));
+            visited_nodes.extend(end_topics.iter().cloned());
+        }
+
+        let start_topic = start_article.get_topic().to_owned();
+        let mut found: Vec<Vec<Hop>> = vec![];
+        let result = match self.strategy {
+            SearchStrategy::Bidirectional if self.article_provider.supports_backlinks() => {
+                self.search_bidirectional(start_article, &*end_article, &end_topics, k, &mut found)
+            }
+            _ => self.search_forward(start_article, &*end_article, &end_topics, k, &mut found),
+        };
+        // The stories found before the search stopped are still good ones.
+        if let (Err(err), true) = (result, found.is_empty()) {
+            return Err(self.story_error(err, end_article.get_topic()));
+        }
+        found.sort_by(|a, b| StoryBuilder::story_order(a).cmp(&StoryBuilder::story_order(b)));
+        found.truncate(k);
+        let articles_fetched = self.articles_fetched();
+        let elapsed = start_time.elapsed();
+        Ok(found
+            .into_iter()
+            .map(|hops| Story {
+                start_topic: start_topic.clone(),
+                end_topic: end_article.get_topic().to_owned(),
+                stats: SearchStats {
+                    // Each hop of a story is one more level of articles.
+                    depth: hops.len() as u8,
+                    articles_fetched,
+                    elapsed,
+                },
+                hops,
+            })
+            .collect())
+    }
+
+    /// Builds a story going from the `start_topic` to the `end_topic` through each of the `waypoints`,
+    /// in order: the shortest stories between consecutive topics are stitched into a single one.
+    /// None of them goes through a topic of the previous ones, and all of them are searched within
+    /// the same limits of the config.
+    pub fn build_story_via(&mut self, start_topic: &str, waypoints: &[&str], end_topic: &str) -> Result<Story, StoryError> {
+        let start_time = Instant::now();
+        if waypoints.iter().any(|waypoint| TitleKey::new(waypoint).is_empty()) {
+            return Err(StoryError::EmptyTopic(TopicRole::Waypoint));
+        }
+        let mut topics = vec![start_topic];
+        topics.extend_from_slice(waypoints);
+        topics.push(end_topic);
+        self.start_search(start_time);
+        let mut stories: Vec<Story> = vec![];
+        for leg in topics.windows(2) {
+            if let Some(story) = stories.last() {
+                // The story goes on from the end of the previous one.
+                self.taken_topics.remove(&TitleKey::new(&story.end_topic));
+            }
+            let story = self.search_stories(leg[0], leg[1], 1, start_time)?.remove(0);
+            self.taken_topics.insert(TitleKey::new(&story.start_topic));
+            self.taken_topics.extend(story.hops.iter().map(|hop| TitleKey::new(&hop.to)));
+            stories.push(story);
+        }
+        let articles_fetched = self.articles_fetched();
+        let start_topic = stories[0].start_topic.clone();
+        let end_topic = stories[stories.len() - 1].end_topic.clone();
+        let mut hops: Vec<Hop> = stories.into_iter().flat_map(|story| story.hops).collect();
+        for (index, hop) in hops.iter_mut().enumerate() {
+            hop.index = index;
+        }
+        Ok(Story {
+            start_topic,
+            end_topic,
+            stats: SearchStats {
+                depth: hops.len().min(u8::MAX as usize) as u8,
+                articles_fetched,
+                elapsed: start_time.elapsed(),
+            },
+            hops,
+        })
+    }
+
+    /// Returns the number of articles fetched from the article provider by the last `build_story`
+    /// (or `build_stories`, or all the searches of the last `build_story_via`).
+    pub fn articles_fetched(&self) -> usize {
+        self.articles_fetched.load(Ordering::SeqCst)
+    }
 
+    pub fn config(&self) -> &StoryBuilderConfig {
+        &self.config
+    }
+
+    /// Changes the limits of the searches of the next stories; see `StoryBuilderConfig`.
+    pub fn set_config(&mut self, config: StoryBuilderConfig) {
+        self.config = config;
+    }
+
+    /// Changes the way `build_story` looks for the shortest story; see `SearchStrategy`.
+    pub fn set_strategy(&mut self, strategy: SearchStrategy) {
+        self.strategy = strategy;
+    }
+
+    fn search_forward(
+        &self,
+        start_article: Box<ThreadedArticle>,
+        end_article: &ThreadedArticle,
+        end_topics: &[TitleKey],
+        k: usize,
+        found: &mut Vec<Vec<Hop>>,
+    ) -> Result<(), SearchError> {
         /* To build a story, we need to build a tree starting at the start_article
            node and going down in a "breadth-first" way; that way, once we find
            the end note, we know it is the shortest path to it. Also, going depth-first
@@ -58,154 +443,530 @@ impl StoryBuilder {
            somewhere in the last level we fetched: */
 
 
-        let mut last_level: Vec<Arc<ArticleNode>> = vec![Arc::new(ArticleNode::new(start_article))]; // starts with start article
-        for i in 0..self.max_depth {
+        let mut last_level: Vec<Arc<ArticleNode>> = vec![Arc::new(ArticleNode::new(Arc::from(start_article)))]; // starts with start article
+        for i in 0..self.config.max_depth {
             // To prevent overloading the system, stop after X level deep
             // Start by loading the next level of articles:
             if i > 0 {
                 // Any other iteration: go one level deeper:
-                let mut current_level = vec![];
-                {
-                    /* Start of threaded scope */
-                    let mut current_level = Arc::new(Mutex::new(&mut current_level));
-
-                    last_level.par_iter().for_each(|article_node| {
-                        /* Iterate on each paragraph of this article, then map on it to
-                       get the article for each related topic in it. */
-                        article_node.get_paragraphs().par_iter().for_each(|paragraph| {
-                            paragraph.topics.par_iter().for_each(|topic| {
-                                // Do not access the same article more than once!!
-                                if !self.visited_nodes.contains(topic) {
-                                    if let Some(article) = self.article_provider.get(topic) {
-                                        let mut new_node = ArticleNode::new(article);
-                                        new_node.attach_to(
-                                            article_node.clone(),
-                                            (&paragraph).text.to_owned(),
-                                        );
-                                        current_level.lock().unwrap().push(Arc::new(new_node));
-                                    }
-                                }
-                            });
-                        });
+                last_level = self.expand_forward(&last_level)?;
+                self.check_frontier(last_level.len())?;
+            }
+
+            // Check if the articles from last_level contain the final topic we are looking for;
+            // the others are the ones the next level is made of.
+            let mut unfinished = vec![];
+            for article_node in last_level {
+                let hops = StoryBuilder::find_paragraph_for_topic_in_article(
+                    article_node.deref().deref(),
+                    end_topics,
+                ).map(|paragraph| {
+                    StoryBuilder::build_hops(
+                        article_node.clone(),
+                        &paragraph.text,
+                        &paragraph.section,
+                        end_article.get_topic(),
+                    )
+                });
+                match hops {
+                    // Found the topic: keep the story.
+                    Some(hops) => StoryBuilder::push_story(found, hops),
+                    None => unfinished.push(article_node),
+                }
+            }
+            // Only stop once the whole level is checked, so that the shortest stories found
+            // do not depend on how many of them were asked for.
+            if found.len() >= k {
+                return Ok(());
+            }
+            last_level = unfinished;
+        }
+
+        Err(SearchError::Exhausted(Budget::Depth))
+    }
+
+    /* The bidirectional search grows two trees at the same time: the forward one starts at the
+       start article and follows the links of each article, while the backward one starts at
+       the end topic and follows backlinks. Each round, the cheapest side is expanded by one level,
+       until a link of the forward frontier reaches a topic of the backward tree. Both trees only
+       need to be about half as deep as a forward search, which is what makes this much faster. */
+    fn search_bidirectional(
+        &self,
+        start_article: Box<ThreadedArticle>,
+        end_article: &ThreadedArticle,
+        end_topics: &[TitleKey],
+        k: usize,
+        found: &mut Vec<Vec<Hop>>,
+    ) -> Result<(), SearchError> {
+        let end_node = Arc::new(BacklinkNode {
+            topic: end_article.get_topic().to_owned(),
+            next: None,
+        });
+        let mut backward_tree = BackwardTree {
+            nodes: HashMap::new(),
+            articles: HashMap::new(),
+        };
+        for topic in end_topics {
+            backward_tree.nodes.insert(topic.clone(), end_node.clone());
+        }
+        let mut backward_level: Vec<Arc<BacklinkNode>> = vec![end_node];
+        let mut forward_level: Vec<Arc<ArticleNode>> = vec![Arc::new(ArticleNode::new(Arc::from(start_article)))];
+        // A story always needs at least one hop: the link where both trees meet.
+        let mut depth: u8 = 1;
+        loop {
+            self.find_meeting_points(&forward_level, &mut backward_tree, end_article, end_topics, found)?;
+            if found.len() >= k {
+                return Ok(());
+            }
+            // Every story goes through the forward frontier; once it is empty, none is left to find.
+            if depth >= self.config.max_depth || forward_level.is_empty() {
+                break;
+            }
+            depth += 1;
+            // Expanding forward costs one fetch per link, while expanding backward costs
+            // one backlinks request per topic; expand the cheapest side.
+            let forward_cost: usize = forward_level
+                .iter()
+                .flat_map(|node| node.get_paragraphs().iter())
+                .map(|paragraph| paragraph.topics.len())
+                .sum();
+            if !backward_level.is_empty() && forward_cost > backward_level.len() {
+                backward_level = self.expand_backward(&backward_level, &mut backward_tree.nodes)?;
+                self.check_frontier(backward_level.len())?;
+            } else {
+                forward_level = self.expand_forward(&forward_level)?;
+                self.check_frontier(forward_level.len())?;
+            }
+        }
+
+        Err(SearchError::Exhausted(Budget::Depth))
+    }
+
+    /// Fetch the articles for every topic linked in the `last_level` and return them as the next level.
+    /// The articles are fetched in parallel, but the next level is always the same: its articles are
+    /// in the order of their links (by parent, then by paragraph, then by position in the paragraph),
+    /// and an article linked more than once is reached through its first link.
+    fn expand_forward(&self, last_level: &Vec<Arc<ArticleNode>>) -> Result<Vec<Arc<ArticleNode>>, SearchError> {
+        // Do not access the same article more than once!! Claim the topics in the order of their
+        // links before fetching any of them, so that the same link always wins.
+        let mut links: Vec<(&Arc<ArticleNode>, &Paragraph, &str, TitleKey)> = vec![];
+        {
+            let mut visited_nodes = self.visited_nodes.lock().unwrap();
+            for article_node in last_level {
+                for paragraph in article_node.get_paragraphs() {
+                    for topic in &paragraph.topics {
+                        let topic_key = TitleKey::new(topic);
+                        if visited_nodes.insert(topic_key.clone()) {
+                            links.push((article_node, paragraph, topic, topic_key));
+                        }
+                    }
+                }
+            }
+        }
+        // The first error encountered by any of the threads, if any:
+        let error: Mutex<Option<SearchError>> = Mutex::new(None);
+        // Collecting a parallel iterator keeps the order of its items.
+        let articles: Vec<Option<Box<ThreadedArticle>>> = links
+            .par_iter()
+            .map(|&(_, _, topic, _)| {
+                // The search stops once this level is done; do not fetch any more articles.
+                if error.lock().unwrap().is_some() {
+                    return None;
+                }
+                match self.fetch_story_node(topic) {
+                    Ok(article) => article,
+                    Err(err) => {
+                        // Keep the first error.
+                        error.lock().unwrap().get_or_insert(err);
+                        None
+                    }
+                }
+            })
+            .collect();
+        if let Some(err) = error.into_inner().unwrap() {
+            return Err(err);
+        }
+        let mut current_level = vec![];
+        let mut visited_nodes = self.visited_nodes.lock().unwrap();
+        for ((article_node, paragraph, _, topic_key), article) in links.into_iter().zip(articles) {
+            let article = match article {
+                Some(article) => article,
+                None => continue,
+            };
+            // The topic may be a redirect to an article that was already reached.
+            let canonical_key = TitleKey::new(article.get_topic());
+            if canonical_key != topic_key && !visited_nodes.insert(canonical_key) {
+                continue;
+            }
+            let mut new_node = ArticleNode::new(Arc::from(article));
+            new_node.attach_to(article_node.clone(), paragraph.text.to_owned(), paragraph.section.clone());
+            current_level.push(Arc::new(new_node));
+        }
+        Ok(current_level)
+    }
+
+    /// Fetch the backlinks of every topic of the `last_level`, add the new ones to the `backward_tree`
+    /// and return them as the next level.
+    fn expand_backward(
+        &self,
+        last_level: &Vec<Arc<BacklinkNode>>,
+        backward_tree: &mut HashMap<TitleKey, Arc<BacklinkNode>>,
+    ) -> Result<Vec<Arc<BacklinkNode>>, SearchError> {
+        self.check_deadline()?;
+        let backlinks: Vec<(Arc<BacklinkNode>, Result<Vec<String>, ProviderError>)> = last_level
+            .par_iter()
+            .map(|node| (node.clone(), self.article_provider.backlinks(&node.topic)))
+            .collect();
+        let mut current_level = vec![];
+        for (node, topics) in backlinks {
+            for topic in topics? {
+                let key = TitleKey::new(&topic);
+                if !backward_tree.contains_key(&key) && !self.taken_topics.contains(&key) && !self.is_avoided(&key) {
+                    let new_node = Arc::new(BacklinkNode {
+                        topic,
+                        next: Some(node.clone()),
                     });
+                    backward_tree.insert(key, new_node.clone());
+                    current_level.push(new_node);
                 }
-                // Then, update the last level with the current level.
-                last_level = current_level;
-            }
-
-            // Check if one of the articles from last_level contains the final topic we are looking for:
-            for article_node in last_level.iter() {
-                if let Some(text) = StoryBuilder::find_text_for_topic_in_article(
-                    article_node.deref().deref().borrow(),
-                    &end_topic,
-                ) {
-                    // Found the topic. Format and return.
-                    return Ok(StoryBuilder::build_final_text(
+            }
+        }
+        Ok(current_level)
+    }
+
+    /// Look for links between the `forward_level` and the `backward_tree`, and add the stories
+    /// going through them to the stories `found`. Every meeting point of the level is used, so
+    /// that the stories found do not depend on how many of them were asked for. Since backlinks
+    /// do not tell which paragraph holds the link, the articles of the backward part are fetched
+    /// to find it; links found outside of the paragraphs (infoboxes, navigation boxes, etc..)
+    /// cannot be used and the next meeting point is tried.
+    fn find_meeting_points(
+        &self,
+        forward_level: &Vec<Arc<ArticleNode>>,
+        backward_tree: &mut BackwardTree,
+        end_article: &ThreadedArticle,
+        end_topics: &[TitleKey],
+        found: &mut Vec<Vec<Hop>>,
+    ) -> Result<(), SearchError> {
+        for article_node in forward_level.iter() {
+            // The article itself may already be part of the backward tree:
+            if let Some(backlink_node) = backward_tree.nodes.get(&TitleKey::new(article_node.get_topic())) {
+                if backlink_node.next.is_some() {
+                    if let Some(story) = self.follow_backlinks(
                         article_node.clone(),
-                        text,
-                        &end_topic,
-                    ));
+                        backlink_node,
+                        &mut backward_tree.articles,
+                        end_article,
+                        end_topics,
+                    )? {
+                        StoryBuilder::push_story(found, story);
+                    }
+                }
+            }
+            // Or one of its links may point to it:
+            for paragraph in StoryBuilder::paragraphs_by_preference(article_node.deref().deref()) {
+                for topic in paragraph.topics.iter() {
+                    let backlink_node = match backward_tree.nodes.get(&TitleKey::new(topic)) {
+                        Some(backlink_node) => backlink_node,
+                        None => continue,
+                    };
+                    let story = if backlink_node.next.is_none() {
+                        // The link points to the end article itself.
+                        Some(StoryBuilder::build_hops(
+                            article_node.clone(),
+                            &paragraph.text,
+                            &paragraph.section,
+                            end_article.get_topic(),
+                        ))
+                    } else if let Some(article) =
+                        self.fetch_backward_node(&backlink_node.topic, &mut backward_tree.articles)?
+                    {
+                        let mut new_node = ArticleNode::new(article);
+                        new_node.attach_to(article_node.clone(), paragraph.text.to_owned(), paragraph.section.clone());
+                        let articles = &mut backward_tree.articles;
+                        self.follow_backlinks(Arc::new(new_node), backlink_node, articles, end_article, end_topics)?
+                    } else {
+                        None
+                    };
+                    if let Some(story) = story {
+                        StoryBuilder::push_story(found, story);
+                    }
                 }
             }
         }
+        Ok(())
+    }
 
+    /// Extend the story ending at `article_node` (which is the article of the `backlink_node`)
+    /// down the backward tree to the end article, fetching each article on the way.
+    fn follow_backlinks(
+        &self,
+        mut article_node: Arc<ArticleNode>,
+        backlink_node: &Arc<BacklinkNode>,
+        backward_articles: &mut BackwardArticles,
+        end_article: &ThreadedArticle,
+        end_topics: &[TitleKey],
+    ) -> Result<Option<Vec<Hop>>, SearchError> {
+        let mut next = backlink_node.next.clone();
+        while let Some(next_node) = next {
+            let next_topics = if next_node.next.is_none() {
+                end_topics.to_vec()
+            } else {
+                vec![TitleKey::new(&next_node.topic)]
+            };
+            let (text, section) = match StoryBuilder::find_paragraph_for_topic_in_article(
+                article_node.deref().deref(),
+                &next_topics,
+            ) {
+                Some(paragraph) => (paragraph.text.to_owned(), paragraph.section.clone()),
+                None => return Ok(None),
+            };
+            if next_node.next.is_none() {
+                return Ok(Some(StoryBuilder::build_hops(
+                    article_node,
+                    &text,
+                    &section,
+                    end_article.get_topic(),
+                )));
+            }
+            let mut new_node = match self.fetch_backward_node(&next_node.topic, backward_articles)? {
+                Some(article) => ArticleNode::new(article),
+                None => return Ok(None),
+            };
+            new_node.attach_to(article_node, text, section);
+            article_node = Arc::new(new_node);
+            next = next_node.next.clone();
+        }
+        Ok(None)
+    }
 
-        Err(
-            format!(
-                "Reached depth of <{}> without finding <{}>. Stopping search.",
-                self.max_depth,
-                end_topic
-            ).to_owned(),
-        )
-    }
-
-    fn build_suggestions_msg(&self, topic: &str) -> String {
-        let mut msg = String::from(format!(
-            "Cannot find wikipedia article for <{}>, try one of the following suggestions:\r\n",
-            topic
-        ));
-        for sugg in self.article_provider.search(topic) {
+    /// Get an article from the article provider, keeping count of the articles fetched.
+    /// Fails without fetching anything once the deadline is reached or the articles budget is spent.
+    fn fetch(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, SearchError> {
+        self.check_deadline()?;
+        let fetched = self.articles_fetched.fetch_add(1, Ordering::SeqCst);
+        if let Some(max_articles) = self.config.max_articles {
+            if fetched >= max_articles {
+                // This article is not fetched after all.
+                self.articles_fetched.fetch_sub(1, Ordering::SeqCst);
+                return Err(SearchError::Exhausted(Budget::Articles));
+            }
+        }
+        Ok(self.article_provider.get(topic)?)
+    }
+
+    /// Fetch the article of a topic the story may go through. Disambiguation pages only list
+    /// the articles sharing a name, which makes for nonsense hops; they are skipped like missing articles,
+    /// as are the articles excluded by the config (which may be reached through a redirect).
+    fn fetch_story_node(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, SearchError> {
+        if self.is_avoided(&TitleKey::new(topic)) {
+            return Ok(None);
+        }
+        Ok(self.fetch(topic)?.filter(|article| {
+            !article.is_disambiguation() && !self.is_avoided(&TitleKey::new(article.get_topic()))
+        }))
+    }
+
+    fn is_avoided(&self, topic: &TitleKey) -> bool {
+        self.config.avoid.iter().any(|rule| rule.excludes(topic))
+    }
+
+    /// Fetch the article of a topic of the backward tree, unless it was already fetched for another
+    /// meeting point (in which case the same article, or lack of one, is returned).
+    fn fetch_backward_node(
+        &self,
+        topic: &str,
+        backward_articles: &mut BackwardArticles,
+    ) -> Result<Option<Arc<ThreadedArticle>>, SearchError> {
+        let key = TitleKey::new(topic);
+        if let Some(article) = backward_articles.get(&key) {
+            return Ok(article.clone());
+        }
+        let article = self.fetch_story_node(topic)?.map(Arc::from);
+        backward_articles.insert(key, article.clone());
+        Ok(article)
+    }
+
+    fn check_deadline(&self) -> Result<(), SearchError> {
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(SearchError::Exhausted(Budget::Deadline)),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_frontier(&self, level_len: usize) -> Result<(), SearchError> {
+        match self.config.max_frontier {
+            Some(max_frontier) if level_len > max_frontier => Err(SearchError::Exhausted(Budget::Frontier)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Build the error returned by `build_story` when the search for the `end_topic` stops with `err`.
+    fn story_error(&self, err: SearchError, end_topic: &str) -> StoryError {
+        let topic = end_topic.to_owned();
+        match err {
+            SearchError::Exhausted(Budget::Depth) => StoryError::DepthExhausted {
+                depth: self.config.max_depth,
+                topic,
+            },
+            SearchError::Exhausted(Budget::Articles) => StoryError::ArticleBudgetExhausted {
+                max_articles: self.config.max_articles.unwrap_or_default(),
+                topic,
+            },
+            SearchError::Exhausted(Budget::Deadline) => StoryError::DeadlineExceeded {
+                timeout: self.config.timeout.unwrap_or_default(),
+                topic,
+            },
+            SearchError::Exhausted(Budget::Frontier) => StoryError::FrontierExhausted {
+                max_frontier: self.config.max_frontier.unwrap_or_default(),
+                topic,
+            },
+            SearchError::Provider(err) => StoryError::Provider(err),
+        }
+    }
+
+    /// Build the error returned when there is no article for the `topic`,
+    /// along with suggestions of similar topics.
+    fn article_not_found(&self, topic: &str) -> StoryError {
+        match self.article_provider.search(topic) {
+            Ok(suggestions) => StoryError::ArticleNotFound {
+                topic: topic.to_owned(),
+                suggestions,
+            },
+            Err(err) => StoryError::Provider(err),
+        }
+    }
+
+    /// Build the error returned when the start or end topic leads to a disambiguation page,
+    /// along with the articles linked by the `article` of that page.
+    fn ambiguous_topic(article: &ThreadedArticle) -> StoryError {
+        let mut candidates: Vec<String> = vec![];
+        for topic in article.get_paragraphs().iter().flat_map(|paragraph| paragraph.topics.iter()) {
+            if !candidates.contains(topic) {
+                candidates.push(topic.clone());
+            }
+        }
+        StoryError::AmbiguousTopic {
+            topic: article.get_topic().to_owned(),
+            candidates,
+        }
+    }
+
+    fn build_suggestions_msg(intro: &str, suggestions: &[String]) -> String {
+        let mut msg = format!("{}\r\n", intro);
+        for sugg in suggestions {
             msg.push_str(&format!("- {}\r\n", &sugg));
         }
 
         msg
     }
 
-    fn find_text_for_topic_in_article<'b>(
+    /// Find the first paragraph of the `article` linking to one of the `topics`,
+    /// preferring paragraphs of prose.
+    fn find_paragraph_for_topic_in_article<'b>(
         article: &'b (ThreadedArticle),
-        topic: &str,
-    ) -> Option<&'b str> {
-        if let Some(paragraph) = article.get_paragraphs().iter().find(|par| {
-            // if any of the topics in the paragraph is <end>, return it.
-            par.topics.iter().any(|t| &t.to_lowercase() == topic)
+        topics: &[TitleKey],
+    ) -> Option<&'b Paragraph> {
+        // if any of the topics in the paragraph is <end>, return it.
+        StoryBuilder::paragraphs_by_preference(article)
+            .into_iter()
+            .find(|par| par.topics.iter().any(|t| topics.contains(&TitleKey::new(t))))
+    }
+
+    /// The paragraphs of the `article`, prose first; list items, table cells and infobox rows
+    /// only tell a story when no paragraph of prose links to the next topic. Among paragraphs of
+    /// the same kind, the ones of the lead section, which sums the article up, come first.
+    fn paragraphs_by_preference<'b>(article: &'b (ThreadedArticle)) -> Vec<&'b Paragraph> {
+        let mut paragraphs: Vec<&Paragraph> = article.get_paragraphs().iter().collect();
+        // The sort is stable; the other paragraphs keep their order.
+        paragraphs.sort_by_key(|paragraph| (paragraph.kind != ParagraphKind::Prose, !paragraph.is_lead()));
+        paragraphs
+    }
+
+    /// Add the story made of the `hops` to the stories `found`, unless one of them already goes
+    /// through the same topics (the same story may be found from both ends of a search), or is a
+    /// shortcut of it. Stories the new one is a shortcut of are removed.
+    fn push_story(found: &mut Vec<Vec<Hop>>, hops: Vec<Hop>) {
+        let topics = StoryBuilder::story_order(&hops).1;
+        if found.iter().any(|story| {
+            let other = StoryBuilder::story_order(story).1;
+            other == topics || StoryBuilder::is_detour(&topics, &other)
         }) {
-            // We found the paragraph; return it directly.
-            return Some(&paragraph.text);
-        } else {
-            None
+            return;
         }
+        found.retain(|story| !StoryBuilder::is_detour(&StoryBuilder::story_order(story).1, &topics));
+        found.push(hops);
+    }
+
+    /// Returns true if the story going through the `longer` topics starts with the topics of the
+    /// `shorter` one, which then links to the end topic right away.
+    fn is_detour(longer: &[&str], shorter: &[&str]) -> bool {
+        shorter.len() < longer.len() && longer.starts_with(&shorter[..shorter.len() - 1])
+    }
+
+    /// The order of the stories returned by `build_stories`: shortest first, then by the topics
+    /// they go through.
+    fn story_order(hops: &[Hop]) -> (usize, Vec<&str>) {
+        (hops.len(), hops.iter().map(|hop| hop.to.as_str()).collect())
     }
 
-    fn build_final_text(
+    /// Walk up the parents of the `article_node` to build the hops of the story, which ends with
+    /// the `final_text` paragraph (found in the `final_section`) of the article, linking to the `final_topic`.
+    fn build_hops(
         article_node: Arc<ArticleNode>,
         final_text: &str,
+        final_section: &[String],
         final_topic: &str,
-    ) -> String {
-        let mut texts: Vec<String> = Vec::new();
-        let mut last_topic = final_topic.to_owned();
-        texts.push(format!("{}\r\n", final_text));
-        {
-            let n: &ArticleNode = article_node.borrow();
-            if let Some(n_text) = n.text() {
-                let new_topic = n.get_topic().to_owned();
-                texts.push(format!("-> ({} to {})\r\n", &new_topic, last_topic));
-                last_topic = new_topic;
-                texts.push(format!("{}\r\n", n_text));
-            }
+    ) -> Vec<Hop> {
+        let mut hops: Vec<Hop> = Vec::new();
+        let mut to = final_topic.to_owned();
+        let mut paragraph = final_text.to_owned();
+        let mut section = final_section.to_vec();
+        let mut node = Some(article_node);
+        while let Some(n) = node {
+            let from = n.get_topic().to_owned();
+            hops.push(Hop {
+                index: 0, // Set once all hops are known.
+                from: from.clone(),
+                to,
+                paragraph,
+                section,
+            });
+            // The text of a node is the paragraph of its parent linking to it.
+            to = from;
+            paragraph = n.text().unwrap_or_default();
+            section = n.section.clone();
+            node = n.parent();
         }
-
-        let mut node = article_node;
-        loop {
-            node = match node.parent() {
-                Some(arc) => {
-                    {
-                        let n: &ArticleNode = arc.borrow();
-                        if let Some(n_text) = n.text() {
-                            let new_topic = n.get_topic().to_owned();
-                            texts.push(format!("-> ({} to {})\r\n", &new_topic, last_topic));
-                            last_topic = new_topic;
-                            texts.push(format!("{}\r\n", n_text));
-                        }
-                    }
-                    arc
-                }
-                _ => {
-                    let new_topic = node.get_topic().to_owned();
-                    texts.push(format!("-> ({} to {})\r\n", &new_topic, last_topic));
-                    return texts.into_iter().rev().collect();
-                }
-            }
+        hops.reverse();
+        for (index, hop) in hops.iter_mut().enumerate() {
+            hop.index = index;
         }
+        hops
     }
 }
 
 struct ArticleNode {
-    data: Box<ThreadedArticle>,
+    data: Arc<ThreadedArticle>,
     parent: Option<Arc<ArticleNode>>,
     text: Option<String>,
+    /// Section of the paragraph of the parent linking to this article.
+    section: Vec<String>,
 }
 
 impl ArticleNode {
-    fn new(data: Box<ThreadedArticle>) -> ArticleNode {
+    fn new(data: Arc<ThreadedArticle>) -> ArticleNode {
         ArticleNode {
             data,
             parent: None,
             text: None,
+            section: vec![],
         }
     }
-    fn attach_to(&mut self, parent: Arc<ArticleNode>, paragraph_text: String) {
+    fn attach_to(&mut self, parent: Arc<ArticleNode>, paragraph_text: String, paragraph_section: Vec<String>) {
         self.parent = Some(parent);
         self.text = Some(paragraph_text);
+        self.section = paragraph_section;
     }
     fn parent(&self) -> Option<Arc<ArticleNode>> {
         self.parent.clone()
@@ -215,11 +976,31 @@ impl ArticleNode {
     }
 }
 
+/// The articles fetched for the topics of the backward tree, by key; `None` for the topics
+/// without an article a story can go through.
+type BackwardArticles = HashMap<TitleKey, Option<Arc<ThreadedArticle>>>;
+
+/// The backward tree of the bidirectional search, growing from the end topic.
+struct BackwardTree {
+    /// Every topic of the tree, by key.
+    nodes: HashMap<TitleKey, Arc<BacklinkNode>>,
+    /// The same meeting points are found again after each expansion of either tree; the articles
+    /// they go through are only fetched the first time.
+    articles: BackwardArticles,
+}
+
+/// A topic of the backward tree of the bidirectional search, along with the
+/// topic it links to on the way to the end topic.
+struct BacklinkNode {
+    topic: String,
+    next: Option<Arc<BacklinkNode>>,
+}
+
 impl<'n> Deref for ArticleNode {
-    type Target = Box<ThreadedArticle>;
+    type Target = ThreadedArticle;
 
-    fn deref(&self) -> &Box<ThreadedArticle> {
-        &self.data
+    fn deref(&self) -> &ThreadedArticle {
+        &*self.data
     }
 }
 
@@ -1,37 +1,164 @@
 use story_builder::story_builder::*;
 use story_builder::article_provider::*;
+use story_builder::exclusion::ExclusionRule;
+use story_builder::story::*;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
 
-static EXPECTED_SUGGESTION: &'static str = "Cannot find wikipedia article for <not-found>, try one of the following suggestions:\r\n\
+static EXPECTED_SUGGESTION_MSG: &'static str = "Cannot find wikipedia article for <not-found>, try one of the following suggestions:\r\n\
                                             - Suggestion 1\r\n\
                                             - Suggestion 2\r\n\
                                             - Suggestion 3\r\n";
 
+fn expected_suggestion_error() -> StoryError {
+    StoryError::ArticleNotFound {
+        topic: "not-found".to_owned(),
+        suggestions: vec![
+            "Suggestion 1".to_owned(),
+            "Suggestion 2".to_owned(),
+            "Suggestion 3".to_owned(),
+        ],
+    }
+}
+
+/// An article of a `GraphProvider`; the text of each paragraph is built from the
+/// topic of the article and the position of the paragraph ("topic paragraph 1").
+/// Topics ending with "(disambiguation)" are disambiguation pages.
+struct GraphArticle {
+    topic: String,
+    paragraphs: Vec<Paragraph>,
+}
+
+impl Article for GraphArticle {
+    fn get_paragraphs(&self) -> &Vec<Paragraph> {
+        &self.paragraphs
+    }
+    fn get_topic(&self) -> &str {
+        &self.topic
+    }
+    fn is_disambiguation(&self) -> bool {
+        self.topic.ends_with("(disambiguation)")
+    }
+}
+
+/// Serves articles from a fixed graph of topics, where each topic has a list of paragraphs
+/// which are lists of linked topics. Topics missing from the graph have no paragraphs.
+/// Counts the articles fetched so tests can check how much of the graph was explored.
+struct GraphProvider {
+    graph: HashMap<&'static str, Vec<Vec<&'static str>>>,
+    with_backlinks: bool,
+    fetched: AtomicUsize,
+}
+
+impl GraphProvider {
+    fn new(graph: Vec<(&'static str, Vec<Vec<&'static str>>)>, with_backlinks: bool) -> GraphProvider {
+        GraphProvider {
+            graph: graph.into_iter().collect(),
+            with_backlinks,
+            fetched: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl ArticleProvider for GraphProvider {
+    fn get(&self, topic: &str) -> Result<Option<Box<Article + Send + Sync>>, ProviderError> {
+        self.fetched.fetch_add(1, Ordering::SeqCst);
+        let paragraphs = self.graph.get(topic).cloned().unwrap_or_default();
+        Ok(Some(Box::new(GraphArticle {
+            topic: topic.to_owned(),
+            paragraphs: paragraphs
+                .into_iter()
+                .enumerate()
+                .map(|(i, topics)| Paragraph {
+                    text: format!("{} paragraph {}", topic, i + 1),
+                    topics: topics.into_iter().map(|t| t.to_owned()).collect(),
+                    index: i,
+                    ..Default::default()
+                })
+                .collect(),
+        })))
+    }
+    fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        panic!("search({}) should not be called in this test.", topic);
+    }
+    fn supports_backlinks(&self) -> bool {
+        self.with_backlinks
+    }
+    fn backlinks(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        assert!(self.with_backlinks, "backlinks() should not be called in this test.");
+        let mut backlinks: Vec<String> = self.graph
+            .iter()
+            .filter(|&(_, paragraphs)| paragraphs.iter().any(|p| p.contains(&topic)))
+            .map(|(&source, _)| source.to_owned())
+            .collect();
+        backlinks.sort();
+        Ok(backlinks)
+    }
+}
+
+/// start links to many dead ends and to `a`, which links to `b`, which links to `end`.
+fn build_wide_graph() -> Vec<(&'static str, Vec<Vec<&'static str>>)> {
+    vec![
+        (
+            "start",
+            vec![
+                vec!["x1", "x2", "x3", "x4", "x5"],
+                vec!["x6", "x7", "x8", "x9", "a"],
+            ],
+        ),
+        ("a", vec![vec!["x1"], vec!["b", "x2"]]),
+        ("b", vec![vec!["end"]]),
+    ]
+}
+
+/// start links to `a` and `b`, which link to `end`, and to `c`, which links to `end` through `d`.
+/// `a` also links to `e`, which links to `end`; going through `e` is a detour.
+fn build_forked_graph() -> Vec<(&'static str, Vec<Vec<&'static str>>)> {
+    vec![
+        ("start", vec![vec!["c"], vec!["b", "a"]]),
+        ("a", vec![vec!["e"], vec!["end"]]),
+        ("b", vec![vec!["end"]]),
+        ("c", vec![vec!["d"]]),
+        ("d", vec![vec!["end"]]),
+        ("e", vec![vec!["end"]]),
+    ]
+}
+
+/// The topics each of the `stories` goes through, end topic included.
+fn topics_of_stories(stories: &[Story]) -> Vec<Vec<&str>> {
+    stories
+        .iter()
+        .map(|story| story.hops.iter().map(|hop| hop.to.as_str()).collect())
+        .collect()
+}
+
 #[test]
 /// For: build_suggestions_msg
 fn build_suggestions_msg_is_working() {
     struct TestProvider {}
     impl ArticleProvider for TestProvider {
         #[allow(unused_variables)]
-        fn get(&self, topic: &str) -> Option<Box<Article + Send + Sync>> {
+        fn get(&self, topic: &str) -> Result<Option<Box<Article + Send + Sync>>, ProviderError> {
             panic!("get() should never be called in this test.");
         }
         #[allow(unused_variables)]
-        fn search(&self, topic: &str) -> Vec<String> {
-            vec![
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+            Ok(vec![
                 "Suggestion 1".to_owned(),
                 "Suggestion 2".to_owned(),
                 "Suggestion 3".to_owned(),
-            ]
+            ])
         }
     }
     let provider = TestProvider {};
     let mut story_builder = StoryBuilder::new(Arc::new(provider));
 
     assert_eq!(
-        story_builder.build_suggestions_msg("not-found"),
-        EXPECTED_SUGGESTION
+        story_builder.article_not_found("not-found").to_string(),
+        EXPECTED_SUGGESTION_MSG
     );
 }
 
@@ -51,20 +178,20 @@ fn build_story_cannot_find_first_article_suggest() {
     }
     struct TestProvider {}
     impl ArticleProvider for TestProvider {
-        fn get(&self, topic: &str) -> Option<Box<Article + Send + Sync>> {
+        fn get(&self, topic: &str) -> Result<Option<Box<Article + Send + Sync>>, ProviderError> {
             if topic == "found" {
-                Some(Box::new(TestArticle { topics: vec![] }))
+                Ok(Some(Box::new(TestArticle { topics: vec![] })))
             } else {
-                None
+                Ok(None)
             }
         }
         #[allow(unused_variables)]
-        fn search(&self, topic: &str) -> Vec<String> {
-            vec![
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+            Ok(vec![
                 "Suggestion 1".to_owned(),
                 "Suggestion 2".to_owned(),
                 "Suggestion 3".to_owned(),
-            ]
+            ])
         }
     }
 
@@ -72,7 +199,7 @@ fn build_story_cannot_find_first_article_suggest() {
     let mut story_builder = StoryBuilder::new(Arc::new(provider));
     assert_eq!(
         story_builder.build_story("not-found", "found"),
-        Err(EXPECTED_SUGGESTION.to_owned())
+        Err(expected_suggestion_error())
     );
 }
 
@@ -92,20 +219,20 @@ fn build_story_cannot_find_second_article_suggest() {
     }
     struct TestProvider {}
     impl ArticleProvider for TestProvider {
-        fn get(&self, topic: &str) -> Option<Box<Article + Send + Sync>> {
+        fn get(&self, topic: &str) -> Result<Option<Box<Article + Send + Sync>>, ProviderError> {
             if topic == "found" {
-                Some(Box::new(TestArticle { topics: vec![] }))
+                Ok(Some(Box::new(TestArticle { topics: vec![] })))
             } else {
-                None
+                Ok(None)
             }
         }
         #[allow(unused_variables)]
-        fn search(&self, topic: &str) -> Vec<String> {
-            vec![
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+            Ok(vec![
                 "Suggestion 1".to_owned(),
                 "Suggestion 2".to_owned(),
                 "Suggestion 3".to_owned(),
-            ]
+            ])
         }
     }
 
@@ -113,7 +240,7 @@ fn build_story_cannot_find_second_article_suggest() {
     let mut story_builder = StoryBuilder::new(Arc::new(provider));
     assert_eq!(
         story_builder.build_story("found", "not-found"),
-        Err(EXPECTED_SUGGESTION.to_owned())
+        Err(expected_suggestion_error())
     );
 }
 
@@ -123,11 +250,11 @@ fn build_story_from_same_start_and_end_should_err() {
     struct TestProvider {}
     impl ArticleProvider for TestProvider {
         #[allow(unused_variables)]
-        fn get(&self, topic: &str) -> Option<Box<Article + Send + Sync>> {
+        fn get(&self, topic: &str) -> Result<Option<Box<Article + Send + Sync>>, ProviderError> {
             panic!("get() should not get called in this test.")
         }
         #[allow(unused_variables)]
-        fn search(&self, topic: &str) -> Vec<String> {
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
             panic!("get() should not get called in this test.")
         }
     }
@@ -135,7 +262,7 @@ fn build_story_from_same_start_and_end_should_err() {
     let mut story_builder = StoryBuilder::new(Arc::new(provider));
     assert_eq!(
         story_builder.build_story("similar topic", "similar topic"),
-        Err("No story to build; same start and end topics.".to_owned())
+        Err(StoryError::SameTopics)
     );
 }
 
@@ -145,11 +272,11 @@ fn build_story_empty_start_topic_should_err() {
     struct TestProvider {}
     impl ArticleProvider for TestProvider {
         #[allow(unused_variables)]
-        fn get(&self, topic: &str) -> Option<Box<Article + Send + Sync>> {
+        fn get(&self, topic: &str) -> Result<Option<Box<Article + Send + Sync>>, ProviderError> {
             panic!("get() should not get called in this test.")
         }
         #[allow(unused_variables)]
-        fn search(&self, topic: &str) -> Vec<String> {
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
             panic!("get() should not get called in this test.")
         }
     }
@@ -157,7 +284,7 @@ fn build_story_empty_start_topic_should_err() {
     let mut story_builder = StoryBuilder::new(Arc::new(provider));
     assert_eq!(
         story_builder.build_story("", "Other topic"),
-        Err("Missing start topic.".to_owned())
+        Err(StoryError::EmptyTopic(TopicRole::Start))
     );
 }
 
@@ -167,11 +294,11 @@ fn build_story_empty_end_topic_should_err() {
     struct TestProvider {}
     impl ArticleProvider for TestProvider {
         #[allow(unused_variables)]
-        fn get(&self, topic: &str) -> Option<Box<Article + Send + Sync>> {
+        fn get(&self, topic: &str) -> Result<Option<Box<Article + Send + Sync>>, ProviderError> {
             panic!("get() should not get called in this test.")
         }
         #[allow(unused_variables)]
-        fn search(&self, topic: &str) -> Vec<String> {
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
             panic!("get() should not get called in this test.")
         }
     }
@@ -179,7 +306,7 @@ fn build_story_empty_end_topic_should_err() {
     let mut story_builder = StoryBuilder::new(Arc::new(provider));
     assert_eq!(
         story_builder.build_story("First topic", ""),
-        Err("Missing end topic.".to_owned())
+        Err(StoryError::EmptyTopic(TopicRole::End))
     );
 }
 
@@ -198,10 +325,12 @@ fn build_story_end_topic_found_in_start_article() {
                     "topic 2".to_owned(),
                     "topic 3".to_owned(),
                 ],
+                ..Default::default()
             },
             Paragraph {
                 text: "Paragraph 2".to_owned(),
-                topics: vec!["topic 3".to_owned(), "END".to_owned(), "topic 5".to_owned()],
+                topics: vec!["topic 3".to_owned(), "End".to_owned(), "topic 5".to_owned()],
+                ..Default::default()
             },
             Paragraph {
                 text: "Paragraph 3".to_owned(),
@@ -210,6 +339,7 @@ fn build_story_end_topic_found_in_start_article() {
                     "topic 1".to_owned(),
                     "topic 5".to_owned(),
                 ],
+                ..Default::default()
             },
         ],
     );
@@ -249,18 +379,18 @@ fn build_story_end_topic_found_in_start_article() {
         }
     }
     impl ArticleProvider for TestProvider {
-        fn get(&self, topic: &str) -> Option<Box<Article + Send + Sync>> {
+        fn get(&self, topic: &str) -> Result<Option<Box<Article + Send + Sync>>, ProviderError> {
             let new_rels: Arc<HashMap<&'static str, Vec<Paragraph>>> = self.prebuilt_rels.clone();
-            Some(Box::new(TestArticle::new(topic.to_owned(), new_rels)))
+            Ok(Some(Box::new(TestArticle::new(topic.to_owned(), new_rels))))
         }
-        fn search(&self, topic: &str) -> Vec<String> {
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
             panic!("search({}) should not be called in this test.", topic);
         }
     }
     let provider = TestProvider::new(prebuilt_rels.clone());
     let mut story_builder = StoryBuilder::new(Arc::new(provider));
     assert_eq!(
-        story_builder.build_story("start", "end"),
+        story_builder.build_story("start", "end").map(|story| render_plain_text(&story)),
         Ok("-> (start to end)\r\nParagraph 2\r\n".to_owned())
     );
 }
@@ -279,6 +409,7 @@ fn build_story_end_topic_found_in_second_level() {
                     "topic 2".to_owned(),
                     "topic 3".to_owned(),
                 ],
+                ..Default::default()
             },
             Paragraph {
                 text: "Paragraph 2".to_owned(),
@@ -287,6 +418,7 @@ fn build_story_end_topic_found_in_second_level() {
                     "topic 2".to_owned(),
                     "topic 4".to_owned(),
                 ],
+                ..Default::default()
             },
             Paragraph {
                 text: "Paragraph 3".to_owned(),
@@ -295,6 +427,7 @@ fn build_story_end_topic_found_in_second_level() {
                     "topic 1".to_owned(),
                     "topic 2".to_owned(),
                 ],
+                ..Default::default()
             },
         ],
     );
@@ -309,13 +442,19 @@ fn build_story_end_topic_found_in_second_level() {
                     "topic 2".to_owned(),
                     "topic 3".to_owned(),
                 ],
+                ..Default::default()
             },
             Paragraph {
                 text: "Paragraph 2".to_owned(),
                 topics: vec!["end".to_owned(), "topic 1".to_owned(), "topic 2".to_owned()],
+                ..Default::default()
             },
         ],
     );
+    // The other topics of the first level are dead ends.
+    for topic in &["topic 2", "topic 3", "topic 4"] {
+        Arc::get_mut(&mut prebuilt_rels).unwrap().insert(topic, vec![]);
+    }
     struct TestArticle {
         topic: String,
         prebuilt_rels: Arc<HashMap<&'static str, Vec<Paragraph>>>,
@@ -352,21 +491,751 @@ fn build_story_end_topic_found_in_second_level() {
         }
     }
     impl ArticleProvider for TestProvider {
-        fn get(&self, topic: &str) -> Option<Box<Article + Send + Sync>> {
+        fn get(&self, topic: &str) -> Result<Option<Box<Article + Send + Sync>>, ProviderError> {
             let new_rels: Arc<HashMap<&'static str, Vec<Paragraph>>> = self.prebuilt_rels.clone();
-            Some(Box::new(TestArticle::new(topic.to_owned(), new_rels)))
+            Ok(Some(Box::new(TestArticle::new(topic.to_owned(), new_rels))))
         }
-        fn search(&self, topic: &str) -> Vec<String> {
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
             panic!("search() should not be called in this test.");
         }
     }
     let provider = TestProvider::new(prebuilt_rels.clone());
     let mut story_builder = StoryBuilder::new(Arc::new(provider));
     assert_eq!(
-        story_builder.build_story("start", "end"),
+        story_builder.build_story("start", "end").map(|story| render_plain_text(&story)),
         Ok(
             "-> (start to topic 1)\r\nParagraph 1\r\n-> (topic 1 to end)\r\nParagraph 2\r\n"
                 .to_owned()
         )
     );
 }
+
+#[test]
+/// For: build_story
+fn build_story_bidirectional_meets_in_the_middle() {
+    let provider = Arc::new(GraphProvider::new(build_wide_graph(), true));
+    let mut story_builder = StoryBuilder::new(provider.clone());
+    assert_eq!(
+        story_builder.build_story("start", "end").map(|story| render_plain_text(&story)),
+        Ok(
+            "-> (start to a)\r\nstart paragraph 2\r\n-> (a to b)\r\na paragraph 2\r\n-> (b to end)\r\nb paragraph 1\r\n"
+                .to_owned()
+        )
+    );
+    // Only start, end, and the articles of the story itself were fetched.
+    assert_eq!(provider.fetched.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+/// For: build_story
+fn build_story_forward_strategy_ignores_backlinks() {
+    let provider = Arc::new(GraphProvider::new(build_wide_graph(), false));
+    let mut story_builder = StoryBuilder::new(provider.clone());
+    story_builder.set_strategy(SearchStrategy::Forward);
+    assert_eq!(
+        story_builder.build_story("start", "end").map(|story| render_plain_text(&story)),
+        Ok(
+            "-> (start to a)\r\nstart paragraph 2\r\n-> (a to b)\r\na paragraph 2\r\n-> (b to end)\r\nb paragraph 1\r\n"
+                .to_owned()
+        )
+    );
+    assert!(provider.fetched.load(Ordering::SeqCst) > 4);
+}
+
+#[test]
+/// For: build_story
+fn build_story_matches_topics_by_title_key() {
+    let provider = GraphProvider::new(
+        vec![("start", vec![vec!["Sat"], vec!["SAT"], vec!["Montre\u{301}al_(city)"]])],
+        false,
+    );
+    let mut story_builder = StoryBuilder::new(Arc::new(provider));
+    // Only the first letter of a title is case-insensitive.
+    assert_eq!(
+        story_builder.build_story("start", "SAT").map(|story| render_plain_text(&story)),
+        Ok("-> (start to SAT)\r\nstart paragraph 2\r\n".to_owned())
+    );
+    // Underscores are spaces, and accents may be written either way.
+    assert_eq!(
+        story_builder.build_story("start", "montréal (city)").map(|story| render_plain_text(&story)),
+        Ok("-> (start to montréal (city))\r\nstart paragraph 3\r\n".to_owned())
+    );
+    assert_eq!(story_builder.build_story("Start", "start_"), Err(StoryError::SameTopics));
+}
+
+#[test]
+/// For: build_story
+fn build_story_from_disambiguation_page_suggests_candidates() {
+    let provider = GraphProvider::new(
+        vec![(
+            "Mercury (disambiguation)",
+            vec![vec!["Mercury (planet)", "Mercury (element)"], vec!["Mercury (planet)"]],
+        )],
+        false,
+    );
+    let mut story_builder = StoryBuilder::new(Arc::new(provider));
+    let expected = StoryError::AmbiguousTopic {
+        topic: "Mercury (disambiguation)".to_owned(),
+        candidates: vec!["Mercury (planet)".to_owned(), "Mercury (element)".to_owned()],
+    };
+    assert_eq!(story_builder.build_story("Mercury (disambiguation)", "end"), Err(expected.clone()));
+    assert_eq!(story_builder.build_story("start", "Mercury (disambiguation)"), Err(expected.clone()));
+    assert_eq!(
+        expected.to_string(),
+        "<Mercury (disambiguation)> may refer to several articles, try one of the following:\r\n\
+         - Mercury (planet)\r\n\
+         - Mercury (element)\r\n"
+    );
+}
+
+#[test]
+/// For: build_story
+fn build_story_skips_disambiguation_pages() {
+    // The shortest story would go through the disambiguation page.
+    let graph = || {
+        vec![
+            ("start", vec![vec!["Mercury (disambiguation)"], vec!["a"]]),
+            ("Mercury (disambiguation)", vec![vec!["end"]]),
+            ("a", vec![vec!["b"]]),
+            ("b", vec![vec!["end"]]),
+        ]
+    };
+    let expected = "-> (start to a)\r\nstart paragraph 2\r\n-> (a to b)\r\na paragraph 1\r\n-> (b to end)\r\nb paragraph 1\r\n";
+    for &with_backlinks in &[false, true] {
+        let mut story_builder = StoryBuilder::new(Arc::new(GraphProvider::new(graph(), with_backlinks)));
+        assert_eq!(
+            story_builder.build_story("start", "end").map(|story| render_plain_text(&story)),
+            Ok(expected.to_owned())
+        );
+    }
+}
+
+#[test]
+/// For: build_story
+fn build_story_never_goes_through_avoided_topics() {
+    // Every shorter story goes through an avoided topic.
+    let graph = || {
+        vec![
+            ("start", vec![vec!["United States", "1984"], vec!["List of things", "a"]]),
+            ("United States", vec![vec!["end"]]),
+            ("1984", vec![vec!["end"]]),
+            ("List of things", vec![vec!["end"]]),
+            ("a", vec![vec!["b"]]),
+            ("b", vec![vec!["end"]]),
+        ]
+    };
+    // The start and end topics themselves are never avoided.
+    let config = ["<countries>", "<years>", "List of *", "start", "end"]
+        .iter()
+        .fold(StoryBuilderConfig::new(), |config, rule| config.avoid(ExclusionRule::parse(rule)));
+    let expected = "-> (start to a)\r\nstart paragraph 2\r\n-> (a to b)\r\na paragraph 1\r\n-> (b to end)\r\nb paragraph 1\r\n";
+    for &with_backlinks in &[false, true] {
+        let provider = Arc::new(GraphProvider::new(graph(), with_backlinks));
+        let mut story_builder = StoryBuilder::with_config(provider.clone(), config.clone());
+        assert_eq!(
+            story_builder.build_story("start", "end").map(|story| render_plain_text(&story)),
+            Ok(expected.to_owned())
+        );
+        // Avoided articles are not even fetched.
+        assert_eq!(provider.fetched.load(Ordering::SeqCst), 4);
+    }
+}
+
+#[test]
+/// For: build_stories
+fn build_stories_returns_shortest_stories_first() {
+    for &with_backlinks in &[false, true] {
+        let mut story_builder = StoryBuilder::new(Arc::new(GraphProvider::new(build_forked_graph(), with_backlinks)));
+        let stories = story_builder.build_stories("start", "end", 2).unwrap();
+        assert_eq!(topics_of_stories(&stories), vec![vec!["a", "end"], vec!["b", "end"]]);
+        assert_eq!(
+            render_plain_text(&stories[1]),
+            "-> (start to b)\r\nstart paragraph 2\r\n-> (b to end)\r\nb paragraph 1\r\n"
+        );
+
+        // There are only three stories without detours.
+        let stories = story_builder.build_stories("start", "end", 10).unwrap();
+        assert_eq!(
+            topics_of_stories(&stories),
+            vec![vec!["a", "end"], vec!["b", "end"], vec!["c", "d", "end"]]
+        );
+        assert_eq!(stories[2].stats.depth, 3);
+        assert_eq!(story_builder.build_stories("start", "end", 0), Ok(vec![]));
+    }
+}
+
+#[test]
+/// For: build_story, build_stories
+fn build_story_is_the_first_of_the_stories() {
+    for &with_backlinks in &[false, true] {
+        let mut story_builder = StoryBuilder::new(Arc::new(GraphProvider::new(build_forked_graph(), with_backlinks)));
+        let story = story_builder.build_story("start", "end").unwrap();
+        assert_eq!(topics_of_stories(::std::slice::from_ref(&story)), vec![vec!["a", "end"]]);
+        for k in 1..5 {
+            let stories = story_builder.build_stories("start", "end", k).unwrap();
+            assert_eq!(stories[0].hops, story.hops);
+        }
+    }
+}
+
+#[test]
+/// For: build_story, build_stories
+fn build_story_is_the_same_on_every_run() {
+    /// Fetches the articles of the first links last, so that the threads finish out of order.
+    struct SlowProvider(GraphProvider);
+    impl ArticleProvider for SlowProvider {
+        fn get(&self, topic: &str) -> Result<Option<Box<Article + Send + Sync>>, ProviderError> {
+            let rank = topic[1..].parse::<u64>().unwrap_or(0);
+            thread::sleep(Duration::from_millis(2 * (5 - rank.min(5))));
+            self.0.get(topic)
+        }
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+            self.0.search(topic)
+        }
+        fn supports_backlinks(&self) -> bool {
+            self.0.supports_backlinks()
+        }
+        fn backlinks(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+            self.0.backlinks(topic)
+        }
+    }
+    // Many articles link to the same topics, and several stories have the same length.
+    let a_links = vec![vec!["b1", "b2"], vec!["b3", "b4"]];
+    let graph = || {
+        vec![
+            ("start", vec![vec!["a1", "a2", "a3"], vec!["a4", "a5"]]),
+            ("a1", a_links.clone()),
+            ("a2", a_links.clone()),
+            ("a3", a_links.clone()),
+            ("a4", a_links.clone()),
+            ("a5", a_links.clone()),
+            ("b1", vec![vec!["x1"], vec!["end"]]),
+            ("b2", vec![vec!["end", "x2"]]),
+            ("b3", vec![vec!["x3", "end"]]),
+            ("b4", vec![vec!["end"]]),
+        ]
+    };
+    for &with_backlinks in &[false, true] {
+        let build = || {
+            let provider = SlowProvider(GraphProvider::new(graph(), with_backlinks));
+            let mut story_builder = StoryBuilder::new(Arc::new(provider));
+            let story = story_builder.build_story("start", "end").map(|story| render_plain_text(&story));
+            let stories: Vec<String> = story_builder
+                .build_stories("start", "end", 3)
+                .unwrap()
+                .iter()
+                .map(render_plain_text)
+                .collect();
+            (story, stories)
+        };
+        let first = build();
+        assert_eq!(
+            first.0,
+            Ok("-> (start to a1)\r\nstart paragraph 1\r\n-> (a1 to b1)\r\na1 paragraph 1\r\n\
+                -> (b1 to end)\r\nb1 paragraph 2\r\n"
+                .to_owned())
+        );
+        for _ in 0..10 {
+            assert_eq!(build(), first);
+        }
+    }
+}
+
+#[test]
+/// For: build_story_via
+fn build_story_via_stitches_stories_through_waypoints() {
+    let provider = GraphProvider::new(
+        vec![
+            ("start", vec![vec!["a"], vec!["end"]]),
+            ("a", vec![vec!["w1"]]),
+            ("w1", vec![vec!["w2", "b"]]),
+            ("w2", vec![vec!["b"]]),
+            ("b", vec![vec!["end"]]),
+        ],
+        true,
+    );
+    let mut story_builder = StoryBuilder::new(Arc::new(provider));
+    let story = story_builder.build_story_via("start", &["w1", "w2"], "end").unwrap();
+    assert_eq!(
+        render_plain_text(&story),
+        "-> (start to a)\r\nstart paragraph 1\r\n-> (a to w1)\r\na paragraph 1\r\n\
+         -> (w1 to w2)\r\nw1 paragraph 1\r\n-> (w2 to b)\r\nw2 paragraph 1\r\n-> (b to end)\r\nb paragraph 1\r\n"
+    );
+    assert_eq!(story.hops.iter().map(|hop| hop.index).collect::<Vec<usize>>(), vec![0, 1, 2, 3, 4]);
+    assert_eq!((story.start_topic.as_str(), story.end_topic.as_str()), ("start", "end"));
+    assert_eq!(story.stats.depth, 5);
+    assert_eq!(story.stats.articles_fetched, story_builder.articles_fetched());
+
+    // Without waypoints, this is the shortest story.
+    assert_eq!(
+        story_builder.build_story_via("start", &[], "end").map(|story| render_plain_text(&story)),
+        Ok("-> (start to end)\r\nstart paragraph 2\r\n".to_owned())
+    );
+}
+
+#[test]
+/// For: build_story_via
+fn build_story_via_reports_errors_of_each_stretch() {
+    let provider = GraphProvider::new(vec![("start", vec![vec!["w"]]), ("w", vec![vec!["x"]])], false);
+    let mut story_builder = StoryBuilder::with_config(Arc::new(provider), StoryBuilderConfig::new().max_depth(2));
+    assert_eq!(
+        story_builder.build_story_via("start", &["w", " "], "end"),
+        Err(StoryError::EmptyTopic(TopicRole::Waypoint))
+    );
+    assert_eq!(
+        story_builder.build_story_via("start", &["w"], "end"),
+        Err(StoryError::DepthExhausted {
+            depth: 2,
+            topic: "end".to_owned(),
+        })
+    );
+    assert_eq!(
+        story_builder.build_story_via("start", &["start"], "end"),
+        Err(StoryError::SameTopics)
+    );
+}
+
+#[test]
+/// For: build_story_via
+fn build_story_via_never_goes_through_a_topic_twice() {
+    // The shortest stories from start to w and from w to end both go through x.
+    let graph = || {
+        vec![
+            ("start", vec![vec!["x"]]),
+            ("x", vec![vec!["w"], vec!["end"]]),
+            ("w", vec![vec!["x", "y"]]),
+            ("y", vec![vec!["end"]]),
+        ]
+    };
+    for &with_backlinks in &[false, true] {
+        let mut story_builder = StoryBuilder::new(Arc::new(GraphProvider::new(graph(), with_backlinks)));
+        let story = story_builder.build_story_via("start", &["w"], "end").unwrap();
+        assert_eq!(topics_of_stories(&[story]), vec![vec!["x", "w", "y", "end"]]);
+        // Each story on its own is still the shortest one.
+        let story = story_builder.build_story("w", "end").unwrap();
+        assert_eq!(topics_of_stories(&[story]), vec![vec!["x", "end"]]);
+    }
+}
+
+#[test]
+/// For: build_story_via
+fn build_story_via_shares_the_articles_budget() {
+    for &with_backlinks in &[false, true] {
+        let provider = GraphProvider::new(vec![("start", vec![vec!["w"]]), ("w", vec![vec!["end"]])], with_backlinks);
+        // Each of both stories only fetches two articles, but together they fetch four.
+        let config = StoryBuilderConfig::new().max_articles(3);
+        let mut story_builder = StoryBuilder::with_config(Arc::new(provider), config);
+        assert!(story_builder.build_story("start", "w").is_ok());
+        assert!(story_builder.build_story("w", "end").is_ok());
+        assert_eq!(
+            story_builder.build_story_via("start", &["w"], "end"),
+            Err(StoryError::ArticleBudgetExhausted {
+                max_articles: 3,
+                topic: "end".to_owned(),
+            })
+        );
+        assert_eq!(story_builder.articles_fetched(), 3);
+    }
+}
+
+#[test]
+/// For: build_stories
+fn build_stories_without_any_story_is_an_error() {
+    let provider = GraphProvider::new(vec![("start", vec![vec!["a"]])], false);
+    let mut story_builder = StoryBuilder::with_config(Arc::new(provider), StoryBuilderConfig::new().max_depth(2));
+    assert_eq!(
+        story_builder.build_stories("start", "end", 3),
+        Err(StoryError::DepthExhausted {
+            depth: 2,
+            topic: "end".to_owned(),
+        })
+    );
+}
+
+#[test]
+/// For: build_story
+fn build_story_bidirectional_respects_max_depth() {
+    let provider = GraphProvider::new(
+        vec![
+            ("start", vec![vec!["a"]]),
+            ("a", vec![vec!["b"]]),
+            ("b", vec![vec!["c"]]),
+            ("c", vec![vec!["d"]]),
+            ("d", vec![vec!["e"]]),
+            ("e", vec![vec!["end"]]),
+        ],
+        true,
+    );
+    let mut story_builder = StoryBuilder::new(Arc::new(provider));
+    assert_eq!(
+        story_builder.build_story("start", "end"),
+        Err(StoryError::DepthExhausted {
+            depth: 5,
+            topic: "end".to_owned(),
+        })
+    );
+}
+
+#[test]
+/// For: build_story
+fn build_story_bidirectional_stops_when_the_forward_tree_ends() {
+    /// Counts the backlinks requests.
+    struct BacklinksCounter(GraphProvider, AtomicUsize);
+    impl ArticleProvider for BacklinksCounter {
+        fn get(&self, topic: &str) -> Result<Option<Box<Article + Send + Sync>>, ProviderError> {
+            self.0.get(topic)
+        }
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+            self.0.search(topic)
+        }
+        fn supports_backlinks(&self) -> bool {
+            self.0.supports_backlinks()
+        }
+        fn backlinks(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+            self.1.fetch_add(1, Ordering::SeqCst);
+            self.0.backlinks(topic)
+        }
+    }
+    // start only links to a dead end, while end can be reached from a long chain of articles.
+    let provider = Arc::new(BacklinksCounter(
+        GraphProvider::new(
+            vec![
+                ("start", vec![vec!["dead end"]]),
+                ("a", vec![vec!["b"]]),
+                ("b", vec![vec!["c"]]),
+                ("c", vec![vec!["end"]]),
+            ],
+            true,
+        ),
+        AtomicUsize::new(0),
+    ));
+    let mut story_builder = StoryBuilder::new(provider.clone());
+    assert_eq!(
+        story_builder.build_story("start", "end"),
+        Err(StoryError::DepthExhausted {
+            depth: 5,
+            topic: "end".to_owned(),
+        })
+    );
+    // No story can be found once the forward tree has no links left to follow.
+    assert_eq!(provider.1.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+/// For: build_story
+fn build_story_fetches_each_article_once() {
+    let provider = Arc::new(GraphProvider::new(
+        vec![
+            ("start", vec![vec!["a", "b"], vec!["a", "c"]]),
+            ("a", vec![vec!["b", "c"], vec!["start"]]),
+            ("b", vec![vec!["c", "a"]]),
+            ("c", vec![vec!["d"]]),
+            ("d", vec![vec!["end"]]),
+        ],
+        false,
+    ));
+    let mut story_builder = StoryBuilder::new(provider.clone());
+    let expected_story = "-> (start to c)\r\nstart paragraph 2\r\n-> (c to d)\r\nc paragraph 1\r\n-> (d to end)\r\nd paragraph 1\r\n";
+    assert_eq!(
+        story_builder.build_story("start", "end").map(|story| render_plain_text(&story)),
+        Ok(expected_story.to_owned())
+    );
+    // start, end, a, b, c and d; each of them only once.
+    assert_eq!(provider.fetched.load(Ordering::SeqCst), 6);
+    assert_eq!(story_builder.articles_fetched(), 6);
+
+    // Building the story again starts from scratch.
+    assert_eq!(
+        story_builder.build_story("start", "end").map(|story| render_plain_text(&story)),
+        Ok(expected_story.to_owned())
+    );
+    assert_eq!(story_builder.articles_fetched(), 6);
+}
+
+#[test]
+/// For: build_story
+fn build_story_bidirectional_fetches_meeting_points_once() {
+    // The meeting point through the disambiguation page is found again after each expansion
+    // of the backward tree, but its article is only fetched the first time.
+    let provider = Arc::new(GraphProvider::new(
+        vec![
+            ("start", vec![vec!["M (disambiguation)", "x1", "x2", "x3", "x4"]]),
+            ("M (disambiguation)", vec![vec!["end"]]),
+            ("x1", vec![vec!["a"]]),
+            ("a", vec![vec!["b"]]),
+            ("b", vec![vec!["end"]]),
+        ],
+        true,
+    ));
+    // start, end, M (disambiguation), x1, a and b; spending more would mean fetching one of them twice.
+    let config = StoryBuilderConfig::new().max_articles(6);
+    let mut story_builder = StoryBuilder::with_config(provider.clone(), config);
+    assert_eq!(
+        story_builder
+            .build_story("start", "end")
+            .map(|story| story.hops.iter().map(|hop| hop.to.clone()).collect::<Vec<String>>()),
+        Ok(vec!["x1".to_owned(), "a".to_owned(), "b".to_owned(), "end".to_owned()])
+    );
+    assert_eq!(provider.fetched.load(Ordering::SeqCst), 6);
+    assert_eq!(story_builder.articles_fetched(), 6);
+}
+
+#[test]
+/// For: build_story
+fn build_story_provider_error_stops_search() {
+    struct TestProvider {}
+    impl ArticleProvider for TestProvider {
+        fn get(&self, topic: &str) -> Result<Option<Box<Article + Send + Sync>>, ProviderError> {
+            match topic {
+                "start" => Ok(Some(Box::new(GraphArticle {
+                    topic: "start".to_owned(),
+                    paragraphs: vec![Paragraph {
+                        text: "Paragraph 1".to_owned(),
+                        topics: vec!["unreachable".to_owned()],
+                        ..Default::default()
+                    }],
+                }))),
+                "end" => Ok(Some(Box::new(GraphArticle {
+                    topic: "end".to_owned(),
+                    paragraphs: vec![],
+                }))),
+                _ => Err(ProviderError::Network("connection reset".to_owned())),
+            }
+        }
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+            panic!("search({}) should not be called in this test.", topic);
+        }
+    }
+    let mut story_builder = StoryBuilder::new(Arc::new(TestProvider {}));
+    assert_eq!(
+        story_builder.build_story("start", "end"),
+        Err(StoryError::Provider(
+            ProviderError::Network("connection reset".to_owned())
+        ))
+    );
+}
+
+#[test]
+/// For: build_story
+fn build_story_returns_hops_and_stats() {
+    let provider = Arc::new(GraphProvider::new(build_wide_graph(), true));
+    let mut story_builder = StoryBuilder::new(provider.clone());
+    let story = story_builder.build_story("start", "end").unwrap();
+    assert_eq!(story.start_topic, "start");
+    assert_eq!(story.end_topic, "end");
+    assert_eq!(
+        story.hops,
+        vec![
+            Hop {
+                index: 0,
+                from: "start".to_owned(),
+                to: "a".to_owned(),
+                paragraph: "start paragraph 2".to_owned(),
+                section: vec![],
+            },
+            Hop {
+                index: 1,
+                from: "a".to_owned(),
+                to: "b".to_owned(),
+                paragraph: "a paragraph 2".to_owned(),
+                section: vec![],
+            },
+            Hop {
+                index: 2,
+                from: "b".to_owned(),
+                to: "end".to_owned(),
+                paragraph: "b paragraph 1".to_owned(),
+                section: vec![],
+            },
+        ]
+    );
+    assert_eq!(story.stats.depth, 3);
+    assert_eq!(story.stats.articles_fetched, 4);
+}
+
+#[test]
+/// For: build_story
+fn build_story_respects_configured_max_depth() {
+    let provider = Arc::new(GraphProvider::new(build_wide_graph(), true));
+    let mut story_builder = StoryBuilder::with_config(provider, StoryBuilderConfig::new().max_depth(2));
+    assert_eq!(
+        story_builder.build_story("start", "end"),
+        Err(StoryError::DepthExhausted {
+            depth: 2,
+            topic: "end".to_owned(),
+        })
+    );
+}
+
+#[test]
+/// For: build_story
+fn build_story_stops_when_articles_budget_is_spent() {
+    let provider = Arc::new(GraphProvider::new(build_wide_graph(), false));
+    let mut story_builder = StoryBuilder::with_config(provider.clone(), StoryBuilderConfig::new().max_articles(5));
+    assert_eq!(
+        story_builder.build_story("start", "end"),
+        Err(StoryError::ArticleBudgetExhausted {
+            max_articles: 5,
+            topic: "end".to_owned(),
+        })
+    );
+    assert_eq!(provider.fetched.load(Ordering::SeqCst), 5);
+    assert_eq!(story_builder.articles_fetched(), 5);
+}
+
+#[test]
+/// For: build_story
+fn build_story_stops_when_frontier_is_too_large() {
+    let provider = Arc::new(GraphProvider::new(build_wide_graph(), false));
+    let mut story_builder = StoryBuilder::with_config(provider, StoryBuilderConfig::new().max_frontier(3));
+    assert_eq!(
+        story_builder.build_story("start", "end"),
+        Err(StoryError::FrontierExhausted {
+            max_frontier: 3,
+            topic: "end".to_owned(),
+        })
+    );
+}
+
+#[test]
+/// For: build_story
+fn build_story_stops_at_deadline() {
+    let provider = Arc::new(GraphProvider::new(build_wide_graph(), true));
+    let mut story_builder = StoryBuilder::new(provider.clone());
+    story_builder.set_config(StoryBuilderConfig::new().timeout(Duration::from_secs(0)));
+    assert_eq!(
+        story_builder.build_story("start", "end"),
+        Err(StoryError::DeadlineExceeded {
+            timeout: Duration::from_secs(0),
+            topic: "end".to_owned(),
+        })
+    );
+    assert_eq!(provider.fetched.load(Ordering::SeqCst), 0);
+}
+
+/// Serves the articles of a fixed map of topics to wikitext, for tests needing whole articles.
+struct WikitextProvider {
+    articles: HashMap<&'static str, &'static str>,
+}
+
+impl ArticleProvider for WikitextProvider {
+    fn get(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, ProviderError> {
+        Ok(self.articles.get(topic).map(|wikitext| {
+            Box::new(GraphArticle {
+                topic: topic.to_owned(),
+                paragraphs: ::wikitext::extract_paragraphs(wikitext),
+            }) as Box<ThreadedArticle>
+        }))
+    }
+    fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+        panic!("search({}) should not be called in this test.", topic);
+    }
+}
+
+#[test]
+/// For: build_story
+fn build_story_keeps_sections_of_hops() {
+    let provider = WikitextProvider {
+        articles: vec![
+            ("start", "The lead of start.\n== History ==\n=== Founding ===\nFounded by [[middle]]."),
+            ("middle", "The lead of middle links to [[end]]."),
+            ("end", "The end."),
+        ].into_iter()
+            .collect(),
+    };
+    let mut story_builder = StoryBuilder::new(Arc::new(provider));
+    let story = story_builder.build_story("start", "end").unwrap();
+    let sections: Vec<&Vec<String>> = story.hops.iter().map(|hop| &hop.section).collect();
+    assert_eq!(sections, vec![&vec!["History".to_owned(), "Founding".to_owned()], &vec![]]);
+    assert_eq!(
+        render_plain_text(&story),
+        "-> (start to middle, from the History > Founding section)\r\nFounded by middle.\r\n\
+         -> (middle to end)\r\nThe lead of middle links to end.\r\n"
+    );
+}
+
+#[test]
+/// For: build_story
+fn build_story_prefers_prose_to_lists() {
+    /// An article made of paragraphs of the given kinds, each linking to a single topic.
+    fn article(topic: &str, paragraphs: Vec<(ParagraphKind, &str, &str)>) -> GraphArticle {
+        GraphArticle {
+            topic: topic.to_owned(),
+            paragraphs: paragraphs
+                .into_iter()
+                .enumerate()
+                .map(|(index, (kind, text, topic))| Paragraph {
+                    text: text.to_owned(),
+                    topics: vec![topic.to_owned()],
+                    kind,
+                    index,
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+    struct ListsProvider;
+    impl ArticleProvider for ListsProvider {
+        fn get(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, ProviderError> {
+            Ok(Some(Box::new(match topic {
+                "start" => article(
+                    "start",
+                    vec![
+                        (ParagraphKind::InfoboxRow, "Capital: end", "end"),
+                        (ParagraphKind::Prose, "The capital of start is end.", "end"),
+                    ],
+                ),
+                _ => article(topic, vec![]),
+            })))
+        }
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+            panic!("search({}) should not be called in this test.", topic);
+        }
+    }
+    let mut story_builder = StoryBuilder::new(Arc::new(ListsProvider));
+    assert_eq!(
+        story_builder.build_story("start", "end").map(|story| render_plain_text(&story)),
+        Ok("-> (start to end)\r\nThe capital of start is end.\r\n".to_owned())
+    );
+}
+
+#[test]
+/// For: build_story
+fn build_story_prefers_the_lead_section() {
+    struct SectionsProvider;
+    impl ArticleProvider for SectionsProvider {
+        fn get(&self, topic: &str) -> Result<Option<Box<ThreadedArticle>>, ProviderError> {
+            let paragraphs = match topic {
+                // Providers do not have to list the paragraphs of the lead first.
+                "start" => vec![
+                    Paragraph {
+                        text: "In 2001, start moved next to end.".to_owned(),
+                        topics: vec!["end".to_owned()],
+                        section: vec!["History".to_owned()],
+                        index: 0,
+                        ..Default::default()
+                    },
+                    Paragraph {
+                        text: "Start is a town close to end.".to_owned(),
+                        topics: vec!["end".to_owned()],
+                        index: 1,
+                        ..Default::default()
+                    },
+                ],
+                _ => vec![],
+            };
+            Ok(Some(Box::new(GraphArticle {
+                topic: topic.to_owned(),
+                paragraphs,
+            })))
+        }
+        fn search(&self, topic: &str) -> Result<Vec<String>, ProviderError> {
+            panic!("search({}) should not be called in this test.", topic);
+        }
+    }
+    let mut story_builder = StoryBuilder::new(Arc::new(SectionsProvider));
+    assert_eq!(
+        story_builder.build_story("start", "end").map(|story| render_plain_text(&story)),
+        Ok("-> (start to end)\r\nStart is a town close to end.\r\n".to_owned())
+    );
+}
@@ -0,0 +1,132 @@
+use std::fmt;
+use unicode_normalization::UnicodeNormalization;
+
+/// The normalized form of an article title: two titles with the same key are the same article.
+/// Keys follow the rules of MediaWiki, so that the topics typed by users, the links of articles,
+/// the titles of an index and the pages of a cache all agree:
+///
+/// * underscores are spaces, and runs of whitespace are a single space;
+/// * the title is in Unicode normalization form C ("Montre\u{301}al" is "Montréal");
+/// * the first letter is uppercase, while the rest is case-sensitive ("SAT" and "Sat" differ).
+///
+/// Titles may contain `%` ("100% (album)"); only the titles taken from URLs are percent-decoded,
+/// by `from_url_path`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TitleKey(String);
+
+impl TitleKey {
+    pub fn new(title: &str) -> TitleKey {
+        let title = title.replace('_', " ");
+        let title: String = title.split_whitespace().collect::<Vec<&str>>().join(" ").nfc().collect();
+        let mut chars = title.chars();
+        match chars.next() {
+            Some(first) => {
+                // Letters without a single uppercase form (such as "ß") are left as is.
+                let mut upper = first.to_uppercase();
+                let first = match (upper.next(), upper.next()) {
+                    (Some(upper), None) => upper,
+                    _ => first,
+                };
+                TitleKey(Some(first).into_iter().chain(chars).collect())
+            }
+            None => TitleKey(title),
+        }
+    }
+
+    /// The key of a title as written in the path of an URL ("Montr%C3%A9al_City"), whose %XX escape
+    /// sequences are decoded.
+    pub fn from_url_path(path: &str) -> TitleKey {
+        TitleKey::new(&percent_decode(path))
+    }
+
+    /// Returns the key as a title, with spaces: "Montreal City".
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the key as written in the URLs of articles, with underscores: "Montreal_City".
+    pub fn to_url_path(&self) -> String {
+        self.0.replace(' ', "_")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<'a> From<&'a str> for TitleKey {
+    fn from(title: &'a str) -> TitleKey {
+        TitleKey::new(title)
+    }
+}
+
+impl fmt::Display for TitleKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Decode the %XX escape sequences of an URL, such as the ones found in hrefs. A `%` that is not
+/// followed by two hexadecimal digits is kept as is.
+pub fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = uri.get(i + 1..i + 3)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Returns true if the `title` is not the one of an article, but of a page in another namespace
+/// ("File:Cat.jpg", "Template talk:Cite") or of another wiki ("fr:Chat", "wikt:purr"). Articles
+/// may have colons in their title too ("Star Wars: A New Hope", "3:10 to Yuma"); only the known
+/// prefixes count.
+pub fn is_namespaced(title: &str) -> bool {
+    // A leading colon ([[:Category:Cats]]) links to the page instead of including it.
+    let title = if title.starts_with(':') { &title[1..] } else { title };
+    let prefix = match title.find(':') {
+        Some(colon) => &title[..colon],
+        None => return false,
+    };
+    let prefix = prefix.replace('_', " ").split_whitespace().collect::<Vec<&str>>().join(" ").to_lowercase();
+    NAMESPACES.binary_search(&prefix.as_str()).is_ok() || INTERWIKIS.binary_search(&prefix.as_str()).is_ok()
+}
+
+/// Namespaces of the English Wikipedia (and their aliases) other than the one of articles, sorted
+/// for binary searches.
+const NAMESPACES: &'static [&'static str] = &[
+    "book", "book talk", "category", "category talk", "draft", "draft talk", "education program",
+    "education program talk", "file", "file talk", "gadget", "gadget definition",
+    "gadget definition talk", "gadget talk", "help", "help talk", "image", "image talk", "media",
+    "mediawiki", "mediawiki talk", "module", "module talk", "portal", "portal talk", "project",
+    "project talk", "special", "talk", "template", "template talk", "timedtext", "timedtext talk",
+    "user", "user talk", "wikipedia", "wikipedia talk", "wp", "wt",
+];
+
+/// Prefixes of the links to the other projects of Wikimedia and to the main editions of Wikipedia,
+/// sorted for binary searches. The one-letter aliases ("w:", "d:") are left out; they clash with
+/// real titles such as "D:Ream".
+const INTERWIKIS: &'static [&'static str] = &[
+    "af", "ar", "arz", "az", "be", "bg", "bn", "br", "bs", "ca", "ceb", "commons", "cs", "cy", "da",
+    "de", "el", "en", "eo", "es", "et", "eu", "fa", "fi", "fr", "ga", "gl", "he", "hi", "hr", "hu",
+    "hy", "id", "is", "it", "ja", "ka", "kk", "ko", "la", "lt", "lv", "mediawikiwiki", "meta",
+    "metawikimedia", "mk", "ml", "ms", "mw", "nl", "nn", "no", "phab", "pl", "pt", "ro", "ru", "sh",
+    "simple", "sk", "sl", "species", "sq", "sr", "sv", "ta", "te", "th", "tl", "tr", "uk", "ur",
+    "uz", "vi", "voy", "war", "wikibooks", "wikidata", "wikimedia", "wikinews", "wikiquote",
+    "wikisource", "wikispecies", "wikiversity", "wikivoyage", "wikt", "wiktionary", "wmf", "zh",
+];
+
+#[cfg(test)]
+mod tests;
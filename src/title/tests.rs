@@ -0,0 +1,74 @@
+use super::*;
+
+#[test]
+fn title_key_of_nothing_is_empty() {
+    assert!(TitleKey::new("").is_empty());
+    assert!(TitleKey::new(" _ ").is_empty());
+}
+
+#[test]
+fn title_key_folds_underscores_and_whitespace() {
+    assert_eq!(TitleKey::new("New_York_City").as_str(), "New York City");
+    assert_eq!(TitleKey::new("  New  York_\tCity _"), TitleKey::new("New York City"));
+}
+
+#[test]
+fn title_key_capitalizes_only_the_first_letter() {
+    assert_eq!(TitleKey::new("montreal"), TitleKey::new("Montreal"));
+    assert_eq!(TitleKey::new("éire").as_str(), "Éire");
+    assert_eq!(TitleKey::new("iPhone").as_str(), "IPhone");
+    assert_eq!(TitleKey::new("ßtraße").as_str(), "ßtraße");
+    assert_ne!(TitleKey::new("SAT"), TitleKey::new("Sat"));
+    assert_ne!(TitleKey::new("montreal"), TitleKey::new("MONTREAL"));
+}
+
+#[test]
+fn title_key_is_in_normalization_form_c() {
+    assert_eq!(TitleKey::new("Montre\u{301}al"), TitleKey::new("Montréal"));
+    assert_eq!(TitleKey::new("montre\u{301}al").as_str(), "Montr\u{E9}al");
+}
+
+#[test]
+fn title_key_keeps_percent_signs() {
+    assert_eq!(TitleKey::new("100% (album)").as_str(), "100% (album)");
+    assert_eq!(TitleKey::new("100%25").as_str(), "100%25");
+    assert_ne!(TitleKey::new("AT%26T"), TitleKey::new("AT&T"));
+}
+
+#[test]
+fn title_key_from_url_path_decodes_urls() {
+    assert_eq!(TitleKey::from_url_path("Montr%C3%A9al_(city)").as_str(), "Montréal (city)");
+    assert_eq!(TitleKey::from_url_path("AT%26T"), TitleKey::new("AT&T"));
+    assert_eq!(TitleKey::from_url_path("100%25_(album)"), TitleKey::new("100% (album)"));
+    assert_eq!(TitleKey::new("New York City").to_url_path(), "New_York_City");
+}
+
+#[test]
+fn percent_decode_is_working() {
+    assert_eq!(percent_decode(""), "");
+    assert_eq!(percent_decode("AT%26T"), "AT&T");
+    assert_eq!(percent_decode("Montr%C3%A9al"), "Montréal");
+    assert_eq!(percent_decode("100%"), "100%");
+    // Exactly two hexadecimal digits; `from_str_radix` alone would accept a sign.
+    assert_eq!(percent_decode("%+1%-1%1G"), "%+1%-1%1G");
+    assert_eq!(percent_decode("%e9%E9"), "\u{FFFD}\u{FFFD}");
+}
+
+#[test]
+fn is_namespaced_only_knows_namespaces_and_interwikis() {
+    for title in &["File:Cat.jpg", ":Category:Cats", "Template_talk:Cite", "fr:Chat", "wikt:purr", "WP:NPOV"] {
+        assert!(is_namespaced(title), "{} is not namespaced", title);
+    }
+    for title in &["Cat", "Star Wars: A New Hope", "Mission: Impossible", "3:10 to Yuma", "Re:Zero", "D:Ream"] {
+        assert!(!is_namespaced(title), "{} is namespaced", title);
+    }
+}
+
+#[test]
+fn namespaces_and_interwikis_are_sorted() {
+    for prefixes in &[NAMESPACES, INTERWIKIS] {
+        let mut sorted = prefixes.to_vec();
+        sorted.sort();
+        assert_eq!(prefixes, &&sorted[..]);
+    }
+}
@@ -0,0 +1,345 @@
+use html::{collapse_whitespace, decode_entities};
+use story_builder::article_provider::{Paragraph, ParagraphKind};
+use title::is_namespaced;
+
+/// Turns the raw MediaWiki markup of an article (the content of the <text> tag of a dump)
+/// into paragraphs of plain text along with the topics linked in each of them.
+/// Templates, references, tables, comments and file/category links are removed, as well as
+/// headings and lists, so that only the prose of the article is kept.
+pub fn extract_paragraphs(wikitext: &str) -> Vec<Paragraph> {
+    extract(wikitext, false)
+}
+
+/// Same as `extract_paragraphs`, but the items of lists linking to other articles are kept too,
+/// as paragraphs of the `ListItem` kind; disambiguation pages are made of those.
+pub fn extract_paragraphs_with_lists(wikitext: &str) -> Vec<Paragraph> {
+    extract(wikitext, true)
+}
+
+/// Returns true if the `wikitext` is the one of a disambiguation page: it holds the
+/// `__DISAMBIG__` magic word, or one of the templates adding it ({{Disambiguation}}, {{Hndis}}, ...).
+pub fn is_disambiguation(wikitext: &str) -> bool {
+    if wikitext.contains("__DISAMBIG__") {
+        return true;
+    }
+    let mut rest = wikitext;
+    while let Some(start) = rest.find("{{") {
+        rest = &rest[start + 2..];
+        let name = rest.split(|c: char| c == '|' || c == '}').next().unwrap_or("");
+        if is_disambiguation_template(&name.trim().replace('_', " ").to_lowercase()) {
+            return true;
+        }
+    }
+    false
+}
+
+fn extract(wikitext: &str, include_lists: bool) -> Vec<Paragraph> {
+    // Remove everything that may span multiple lines first:
+    let text = strip_delimited(wikitext, "<!--", "-->");
+    let text = strip_refs(&text);
+    let text = strip_nested(&text, "{{", "}}");
+    let text = strip_nested(&text, "{|", "|}");
+    let text = strip_tags(&text);
+    let text = strip_quotes(&text);
+
+    // Then, group consecutive lines of prose into paragraphs:
+    let mut paragraphs: Vec<Paragraph> = vec![];
+    let mut block = String::new();
+    // The headings of the current section and of its parents, along with their levels:
+    let mut sections: Vec<(usize, String)> = vec![];
+    for line in text.lines().map(|line| line.trim()) {
+        if is_prose(line) {
+            if !block.is_empty() {
+                block.push(' '); // Single line breaks are rendered as spaces.
+            }
+            block.push_str(line);
+            continue;
+        }
+        if !block.is_empty() {
+            paragraphs.push(extract_paragraph(&block, &sections));
+            block.clear();
+        }
+        if include_lists && line.starts_with(|c: char| c == '*' || c == '#') {
+            let mut item = extract_paragraph(line.trim_start_matches(|c: char| "*#:;".contains(c)), &sections);
+            item.kind = ParagraphKind::ListItem;
+            // Items without links do not lead anywhere.
+            if !item.topics.is_empty() {
+                paragraphs.push(item);
+            }
+            continue;
+        }
+        if let Some((level, heading)) = parse_heading(line) {
+            // The heading closes the sections of the same level or deeper.
+            while sections.last().map_or(false, |&(last_level, _)| last_level >= level) {
+                sections.pop();
+            }
+            sections.push((level, heading));
+        }
+    }
+    if !block.is_empty() {
+        paragraphs.push(extract_paragraph(&block, &sections));
+    }
+    // Blocks made only of skipped links (images, categories, etc..) end up empty.
+    paragraphs.retain(|paragraph| !paragraph.text.is_empty());
+    for (index, paragraph) in paragraphs.iter_mut().enumerate() {
+        paragraph.index = index;
+    }
+    paragraphs
+}
+
+/// Returns the title of the target article if the `wikitext` is a redirect page
+/// (#REDIRECT [[Target]]), or `None` otherwise.
+pub fn redirect_target(wikitext: &str) -> Option<String> {
+    let wikitext = wikitext.trim();
+    match wikitext.get(.."#REDIRECT".len()) {
+        Some(keyword) if keyword.eq_ignore_ascii_case("#REDIRECT") => (),
+        _ => return None,
+    }
+    let start = wikitext.find("[[")? + 2;
+    let end = start + wikitext[start..].find("]]")?;
+    // Redirects may point to a section of the target article (Target#Section).
+    let target = wikitext[start..end].split(|c| c == '|' || c == '#').next()?.trim();
+    if target.is_empty() {
+        None
+    } else {
+        Some(target.replace("_", " "))
+    }
+}
+
+/// Returns the level and the text of the heading if the `line` is one (== History == is
+/// at level 2, === Early years === at level 3, etc..), or `None` otherwise.
+fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let opening = line.chars().take_while(|&c| c == '=').count();
+    let closing = line.chars().rev().take_while(|&c| c == '=').count();
+    if opening == 0 || closing == 0 || line.len() <= opening + closing {
+        return None;
+    }
+    // Unbalanced equal signs are part of the heading text.
+    let level = opening.min(closing);
+    let heading = extract_links(line[level..line.len() - level].trim()).text;
+    if heading.is_empty() {
+        None
+    } else {
+        Some((level, heading))
+    }
+}
+
+/// Build the paragraph of the `block` of prose found in the given `sections`.
+fn extract_paragraph(block: &str, sections: &[(usize, String)]) -> Paragraph {
+    let mut paragraph = extract_links(block);
+    paragraph.section = sections.iter().map(|&(_, ref heading)| heading.clone()).collect();
+    paragraph
+}
+
+/// Returns true if the lowercased `name` is the one of a template marking disambiguation pages,
+/// such as "disambiguation", "dab" or "place name disambiguation".
+fn is_disambiguation_template(name: &str) -> bool {
+    match name {
+        "dab" | "disamb" | "disambig" | "hndis" | "geodis" => true,
+        _ => name == "disambiguation" || name.ends_with(" disambiguation"),
+    }
+}
+
+/// A line is considered prose unless it is empty, a heading, a list item,
+/// a leftover table row or a magic word (such as __TOC__).
+fn is_prose(line: &str) -> bool {
+    !line.is_empty() && !line.starts_with(|c: char| "=*#:;|!".contains(c))
+        && !(line.starts_with("__") && line.ends_with("__"))
+}
+
+/// Replace each [[Target|label]] link of the `block` by its label and register its target
+/// as a topic of the paragraph. Links with a namespace (other wikis, help pages, etc..) never
+/// become topics, unlike articles with a colon in their title ("3:10 to Yuma"); files and categories are removed entirely, and [http://... label] external
+/// links are replaced by their label.
+fn extract_links(block: &str) -> Paragraph {
+    let mut paragraph = Paragraph::default();
+    let mut rest = block;
+    while let Some(start) = rest.find('[') {
+        paragraph.text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with("[[") {
+            // Captions of files may contain links themselves; find the matching ]].
+            let end = match find_closing(&rest[2..], "[[", "]]") {
+                Some(end) => end + 2,
+                None => break, // Unclosed link; keep the rest as text.
+            };
+            let link = &rest[2..end];
+            rest = &rest[end + 2..];
+            let mut parts = link.splitn(2, '|');
+            let target = parts.next().unwrap_or("").trim();
+            let label = parts.next().unwrap_or("");
+            if is_namespaced(target) {
+                if !is_media_link(target) {
+                    paragraph.text.push_str(label);
+                }
+                continue;
+            }
+            // A leading colon only makes sure the target is linked to ([[:Cat]]).
+            let target = if target.starts_with(':') { &target[1..] } else { target };
+            paragraph.text.push_str(if label.is_empty() { target } else { label });
+            // Links to a section of the same article (#History) have no topic.
+            let target = target.split('#').next().unwrap_or("").trim();
+            if !target.is_empty() {
+                paragraph.topics.push(target.replace("_", " "));
+            }
+        } else if rest[1..].starts_with("http") || rest[1..].starts_with("//") {
+            let end = match rest.find(']') {
+                Some(end) => end,
+                None => break,
+            };
+            // The label follows the URL, separated by a space:
+            if let Some(space) = rest[..end].find(' ') {
+                paragraph.text.push_str(&rest[space + 1..end]);
+            }
+            rest = &rest[end + 1..];
+        } else {
+            paragraph.text.push('[');
+            rest = &rest[1..];
+        }
+    }
+    paragraph.text.push_str(rest);
+    // Wikitext may use HTML entities (&nbsp;, &ndash;, ...) as well.
+    paragraph.text = collapse_whitespace(&decode_entities(&paragraph.text));
+    paragraph
+}
+
+/// Returns true if the `target` of a link is a file, image or category; those links are
+/// not part of the text.
+fn is_media_link(target: &str) -> bool {
+    // A leading colon ([[:Category:Cats]]) links to the page instead of including it.
+    let target = if target.starts_with(':') { &target[1..] } else { target };
+    let namespace = target.split(':').next().unwrap_or("");
+    match namespace.to_lowercase().as_str() {
+        "file" | "image" | "media" | "category" => true,
+        _ => false,
+    }
+}
+
+/// Returns the position of the `close` delimiter matching an `open` delimiter
+/// that was found right before the start of `text`.
+fn find_closing(text: &str, open: &str, close: &str) -> Option<usize> {
+    let mut depth = 1;
+    let mut pos = 0;
+    loop {
+        let next_open = text[pos..].find(open).map(|i| i + pos);
+        let next_close = text[pos..].find(close).map(|i| i + pos)?;
+        match next_open {
+            Some(o) if o < next_close => {
+                depth += 1;
+                pos = o + open.len();
+            }
+            _ => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(next_close);
+                }
+                pos = next_close + close.len();
+            }
+        }
+    }
+}
+
+/// Remove all (possibly nested) blocks starting with `open` and ending with `close`,
+/// such as {{templates}} and {| tables |}. An unclosed block is removed up to the end.
+fn strip_nested(text: &str, open: &str, close: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(open) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start + open.len()..];
+        match find_closing(rest, open, close) {
+            Some(end) => rest = &rest[end + close.len()..],
+            None => return result,
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Remove all blocks starting with `open` and ending with `close`, such as <!-- comments -->.
+fn strip_delimited(text: &str, open: &str, close: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(open) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start + open.len()..];
+        match rest.find(close) {
+            Some(end) => rest = &rest[end + close.len()..],
+            None => return result,
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Remove <ref>references</ref>, whether they have content or are self-closing (<ref name="a" />).
+fn strip_refs(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("<ref") {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let tag_end = match rest.find('>') {
+            Some(tag_end) => tag_end,
+            None => return result,
+        };
+        if rest[..tag_end].ends_with('/') {
+            rest = &rest[tag_end + 1..];
+        } else {
+            match rest.find("</ref>") {
+                Some(end) => rest = &rest[end + "</ref>".len()..],
+                None => return result,
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Remove the remaining HTML tags (<small>, <br />, <span ...>, etc..) but keep their content.
+fn strip_tags(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        // Only consider it a tag if it looks like one; "x < y" is plain text.
+        let is_tag = rest[1..].starts_with(|c: char| c.is_alphabetic() || c == '/');
+        match rest.find('>') {
+            Some(end) if is_tag => rest = &rest[end + 1..],
+            _ => {
+                result.push('<');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Remove the ''italic'' and '''bold''' quotes, keeping plain apostrophes.
+fn strip_quotes(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut quotes = 0;
+    for c in text.chars().chain(Some('\0')) {
+        if c == '\'' {
+            quotes += 1;
+            continue;
+        }
+        match quotes {
+            // A single quote is a plain apostrophe; four quotes are an apostrophe followed by bold.
+            1 | 4 => result.push('\''),
+            // Five quotes are both bold and italic; anything more is literal apostrophes.
+            n if n > 5 => result.push_str(&"'".repeat(n - 5)),
+            _ => (),
+        }
+        quotes = 0;
+        if c != '\0' {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests;
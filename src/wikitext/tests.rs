@@ -0,0 +1,167 @@
+use super::*;
+
+#[test]
+fn extract_paragraphs_from_nothing_returns_nothing() {
+    assert!(extract_paragraphs("").is_empty());
+}
+
+#[test]
+fn extract_paragraphs_splits_on_empty_lines_and_headings() {
+    let paragraphs = extract_paragraphs(
+        "First line\nof the lead.\n\nSecond paragraph.\n== History ==\nThird paragraph.",
+    );
+    let texts: Vec<&str> = paragraphs.iter().map(|p| p.text.as_str()).collect();
+    assert_eq!(
+        texts,
+        vec!["First line of the lead.", "Second paragraph.", "Third paragraph."]
+    );
+}
+
+#[test]
+fn extract_paragraphs_keeps_sections_and_positions() {
+    let paragraphs = extract_paragraphs(
+        "The lead.\n\n== History ==\nFounded in 1642.\n=== 20th [[century]] ===\nExpo 67.\n\
+         ==== Olympics ====\n\n[[File:Stadium.jpg]]\n\nThe 1976 games.\n== Geography ==\nOn an island.",
+    );
+    let sections: Vec<(usize, Vec<&str>, bool)> = paragraphs
+        .iter()
+        .map(|p| (p.index, p.section.iter().map(|s| s.as_str()).collect(), p.is_lead()))
+        .collect();
+    assert_eq!(
+        sections,
+        vec![
+            (0, vec![], true),
+            (1, vec!["History"], false),
+            (2, vec!["History", "20th century"], false),
+            (3, vec!["History", "20th century", "Olympics"], false),
+            (4, vec!["Geography"], false),
+        ]
+    );
+}
+
+#[test]
+fn extract_paragraphs_decodes_entities() {
+    let paragraphs = extract_paragraphs("[[Ottawa]]&nbsp;is the capital of [[Canada]] &ndash; since 1857 &amp;  still.");
+    assert_eq!(paragraphs[0].text, "Ottawa is the capital of Canada – since 1857 & still.");
+}
+
+#[test]
+fn extract_paragraphs_skips_lists_tables_and_magic_words() {
+    let paragraphs = extract_paragraphs(
+        "__NOTOC__\n* [[Item]]\n# [[Other item]]\n{| class=\"wikitable\"\n|-\n| [[Cell]] || {{flag|Canada}}\n|}\nProse.",
+    );
+    assert_eq!(paragraphs.len(), 1);
+    assert_eq!(paragraphs[0].text, "Prose.");
+    assert!(paragraphs[0].topics.is_empty());
+}
+
+#[test]
+fn extract_paragraphs_with_lists_keeps_linked_items() {
+    let paragraphs = extract_paragraphs_with_lists(
+        "'''Mercury''' may refer to:\n== Science ==\n* [[Mercury (planet)]], the closest planet to the Sun\n\
+         ** [[Mercury (element)|Mercury]], a metal\n* An item without links\n{{Disambiguation}}",
+    );
+    let items: Vec<(&str, ParagraphKind, Vec<&str>)> = paragraphs
+        .iter()
+        .map(|p| (p.text.as_str(), p.kind, p.section.iter().map(|s| s.as_str()).collect()))
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            ("Mercury may refer to:", ParagraphKind::Prose, vec![]),
+            ("Mercury (planet), the closest planet to the Sun", ParagraphKind::ListItem, vec!["Science"]),
+            ("Mercury, a metal", ParagraphKind::ListItem, vec!["Science"]),
+        ]
+    );
+    assert_eq!(paragraphs[2].topics, vec!["Mercury (element)"]);
+    assert_eq!(paragraphs[2].index, 2);
+}
+
+#[test]
+fn is_disambiguation_finds_magic_words_and_templates() {
+    assert!(is_disambiguation("'''Mercury''' may refer to:\n__DISAMBIG__"));
+    assert!(is_disambiguation("* [[Mercury (planet)]]\n{{disambiguation|geo}}"));
+    assert!(is_disambiguation("* [[John Smith (actor)]]\n{{ Human_name disambiguation }}"));
+    assert!(is_disambiguation("* [[Paris, Texas]]\n{{Dab}}"));
+    assert!(!is_disambiguation("'''Mercury''' is a planet.{{About|the planet|other uses|Mercury (disambiguation)}}"));
+    assert!(!is_disambiguation("{{Disambiguation needed}} text"));
+}
+
+#[test]
+fn extract_paragraphs_strips_templates_refs_and_comments() {
+    let paragraphs = extract_paragraphs(
+        "{{Infobox city\n| name = {{lang|fr|Montréal}}\n| mayor = [[Valérie Plante]]\n}}\n\
+         '''Montreal'''<!-- [[Not a link]] --> is a city<ref name=\"a\">{{cite web|url=x}}</ref> \
+         in [[Quebec]].<ref name=\"b\" /><br />",
+    );
+    assert_eq!(paragraphs.len(), 1);
+    assert_eq!(paragraphs[0].text, "Montreal is a city in Quebec.");
+    assert_eq!(paragraphs[0].topics, vec!["Quebec"]);
+}
+
+#[test]
+fn extract_paragraphs_resolves_links() {
+    let paragraphs = extract_paragraphs(
+        "A [[Carnivore|carnivorous]] [[mammal]]s from [[New_York_City#History|the city]], \
+         see [[#Behaviour|below]] and [http://example.com the web].",
+    );
+    assert_eq!(
+        paragraphs[0].text,
+        "A carnivorous mammals from the city, see below and the web."
+    );
+    assert_eq!(
+        paragraphs[0].topics,
+        vec!["Carnivore", "mammal", "New York City"]
+    );
+}
+
+#[test]
+fn extract_paragraphs_drops_namespaced_links() {
+    let paragraphs = extract_paragraphs(
+        "[[File:Cat.jpg|thumb|A [[cat]] sleeping]]\n\n\
+         The [[cat]] [[wikt:purr|purrs]].[[Category:Felines]][[fr:Chat]]",
+    );
+    assert_eq!(paragraphs.len(), 1);
+    assert_eq!(paragraphs[0].text, "The cat purrs.");
+    assert_eq!(paragraphs[0].topics, vec!["cat"]);
+}
+
+#[test]
+fn extract_paragraphs_keeps_articles_with_colons() {
+    let paragraphs = extract_paragraphs(
+        "[[Star Wars: A New Hope]], [[Mission: Impossible (film)|Mission: Impossible]] \
+         and [[3:10 to Yuma]] are films; see [[:Help:Links|help]].",
+    );
+    assert_eq!(
+        paragraphs[0].text,
+        "Star Wars: A New Hope, Mission: Impossible and 3:10 to Yuma are films; see help."
+    );
+    assert_eq!(
+        paragraphs[0].topics,
+        vec!["Star Wars: A New Hope", "Mission: Impossible (film)", "3:10 to Yuma"]
+    );
+}
+
+#[test]
+fn strip_quotes_keeps_apostrophes() {
+    assert_eq!(
+        strip_quotes("''Italic'', '''bold''', '''''both''''' and the cat's '''''''toy"),
+        "Italic, bold, both and the cat's ''toy"
+    );
+    assert_eq!(strip_quotes("''''Cat''''s"), "'Cat's");
+}
+
+#[test]
+fn strip_nested_handles_unclosed_blocks() {
+    assert_eq!(strip_nested("a{{b{{c}}d}}e{{f", "{{", "}}"), "ae");
+}
+
+#[test]
+fn redirect_target_is_extracted() {
+    assert_eq!(
+        redirect_target("#REDIRECT [[New_York_City#History]]\n{{R from abbreviation}}"),
+        Some("New York City".to_owned())
+    );
+    assert_eq!(redirect_target("#redirect[[Montreal]]"), Some("Montreal".to_owned()));
+    assert_eq!(redirect_target("'''Montreal''' is a [[city]]."), None);
+}
@@ -1,11 +1,26 @@
 extern crate xml;
 
-use std::io::{BufReader, Read, Write, Seek, SeekFrom};
+use std::io::{self, BufRead, BufReader, Read, Write, Seek, SeekFrom};
+use std::collections::HashMap;
 use self::xml::reader::*;
+use wikitext::extract_paragraphs;
+
+/// A single article read back from the XML dump.
+pub struct WikiPage {
+    pub title: String,
+    /// Raw wikitext found in the <text> tag of the page.
+    pub text: String,
+}
 
 /// This function takes a Reader `data_source`, consumes it,
 /// indexes it and outputs the result in the `index_out` Writer.
-pub fn generate_index<R: Read + Seek, W: Read + Write + Seek>(mut data_source: R, mut index_out: W) {
+/// Redirect pages are not indexed; instead, a `Source title\tTarget title` line is written
+/// for each of them in the `redirects_out` Writer (titles cannot contain tabs).
+pub fn generate_index<R: Read + Seek, W: Read + Write + Seek, V: Write>(
+    mut data_source: R,
+    mut index_out: W,
+    mut redirects_out: V,
+) -> io::Result<()> {
     /* To allow resuming indexation process, read the last indexed position from the index and
        seek to it: */
     let seek_resume = get_last_index_position(&mut index_out);
@@ -14,13 +29,14 @@ pub fn generate_index<R: Read + Seek, W: Read + Write + Seek>(mut data_source: R
         println!("Seeking to {}", seek_resume);
         must_skip_one = true;
     }
-    data_source.seek(SeekFrom::Start(seek_resume));
+    data_source.seek(SeekFrom::Start(seek_resume))?;
     println!("Indexing...");
     let mut xml_reader = EventReader::new(BufReader::new(data_source));
     // Skip the next article since it was already in the index
-    if (must_skip_one) {
+    // (redirects found after it may be written a second time, which is harmless).
+    if must_skip_one {
         skip_until_page_start(&mut xml_reader);
-        xml_reader.next();
+        let _ = xml_reader.next();
     }
     // The XML file is made of lots of tags, but we only want to keep <page> events.
     // Skip to the next article (<page>) start:
@@ -32,12 +48,46 @@ pub fn generate_index<R: Read + Seek, W: Read + Write + Seek>(mut data_source: R
                 // Only consider namespace 0 (default articles)
                 // Skip the others (files, templates, gadgets, etc..)
                 if ns == "0" {
-                    index_out.write(format!("{},{}\r\n", page_pos, title).as_bytes()).expect("Cannot write to index file. Aborting.");
+                    match extract_next_redirect(&mut xml_reader) {
+                        Some(target) => write!(redirects_out, "{}\t{}\r\n", title, target)?,
+                        None => write!(index_out, "{},{}\r\n", page_pos, title)?,
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+/// This function takes a Reader `data_source`, consumes it, and outputs a
+/// `Target title\tSource title` line in the `backlinks_out` Writer for each article linked
+/// in the paragraphs of another article. Targets are written as they appear in the links;
+/// they may be redirects.
+pub fn generate_backlinks<R: Read + Seek, W: Write>(data_source: R, mut backlinks_out: W) -> io::Result<()> {
+    println!("Indexing backlinks...");
+    let mut xml_reader = EventReader::new(BufReader::new(data_source));
+    while skip_until_page_start(&mut xml_reader).is_some() {
+        if let Some(title) = extract_next_title(&mut xml_reader) {
+            if let Some(ns) = extract_next_namespace(&mut xml_reader) {
+                // Only articles can be part of a story; redirects have no paragraphs.
+                if ns == "0" && extract_next_redirect(&mut xml_reader).is_none() {
+                    if let Some(text) = extract_next_text(&mut xml_reader) {
+                        let mut targets: Vec<String> = extract_paragraphs(&text)
+                            .into_iter()
+                            .flat_map(|paragraph| paragraph.topics.into_iter())
+                            .collect();
+                        targets.sort();
+                        targets.dedup();
+                        for target in targets {
+                            write!(backlinks_out, "{}\t{}\r\n", target, title)?;
+                        }
+                    }
                 }
             }
         }
     }
+    Ok(())
 }
+
 /// Iterate over all XmlEvents in the `reader` until a <page> is found.
 /// If an error occurs while reading, or the end of the file is reached,
 /// `None` is returned.
@@ -104,11 +154,34 @@ fn extract_next_namespace<R: Read>(reader: &mut EventReader<R>) -> Option<String
     return None;
 }
 
+/// Read the events following the namespace of a page until its <revision> starts, and return
+/// the title of the target article if the page is a redirect (<redirect title="..." />).
+fn extract_next_redirect<R: Read>(reader: &mut EventReader<R>) -> Option<String> {
+    while let Ok(event) = reader.next() {
+        if let XmlEvent::StartElement{name, attributes, ..} = event {
+            match name.local_name.as_str() {
+                "redirect" => {
+                    return attributes
+                        .into_iter()
+                        .find(|attr| attr.name.local_name == "title")
+                        .map(|attr| attr.value);
+                }
+                // The redirect tag always comes before the revision; this is a regular article.
+                "revision" => return None,
+                _ => (),
+            }
+        }
+    }
+    return None;
+}
+
 fn get_last_index_position<W: Read + Write + Seek>(index_in: &mut W) -> u64 {
     // Go back 4k characters and read the lines:
-    const CHUNK_SIZE: usize = 4096;
-    let start_pos = index_in.seek(SeekFrom::End(-1*CHUNK_SIZE as i64)).expect("Cannot seek in index file. Aborting.");
-    let mut buf = String::with_capacity(CHUNK_SIZE);
+    const CHUNK_SIZE: u64 = 4096;
+    let file_len = index_in.seek(SeekFrom::End(0)).expect("Cannot seek in index file. Aborting.");
+    // Smaller index files are read from the start.
+    let start_pos = index_in.seek(SeekFrom::Start(file_len.saturating_sub(CHUNK_SIZE))).expect("Cannot seek in index file. Aborting.");
+    let mut buf = String::with_capacity(CHUNK_SIZE as usize);
     index_in.read_to_string(&mut buf).expect("Unable to read index file. Aborting.");
     let lines: Vec<&str> = buf.split("\r\n").filter(|x| x.len() > 0 /* Ignore empty lines */).collect();
     let line_count = lines.len();
@@ -130,5 +203,85 @@ fn get_last_index_position<W: Read + Write + Seek>(index_in: &mut W) -> u64 {
     }
 }
 
+/// Reads an index previously written by `generate_index` and returns a map of
+/// article titles to their seek position in the XML dump.
+pub fn read_index<R: Read>(index_in: R) -> HashMap<String, u64> {
+    let mut index = HashMap::new();
+    for line in BufReader::new(index_in).lines() {
+        let line = line.expect("Unable to read index file. Aborting.");
+        // The lines are built as: SeekIndex,ArticleName
+        // Only split on the first comma; article names may contain some.
+        let mut parts = line.splitn(2, ',');
+        if let (Some(pos), Some(title)) = (parts.next(), parts.next()) {
+            if let Ok(pos) = pos.parse() {
+                index.insert(title.to_owned(), pos);
+            }
+        }
+    }
+    index
+}
+
+/// Seeks the `data_source` to `page_pos` (a position written in the index by `generate_index`)
+/// and reads the <page> found there. Returns `None` if no complete page can be read.
+pub fn read_page_at<R: Read + Seek>(mut data_source: R, page_pos: u64) -> Option<WikiPage> {
+    data_source.seek(SeekFrom::Start(page_pos)).ok()?;
+    let mut xml_reader = EventReader::new(BufReader::new(data_source));
+    let title = extract_next_title(&mut xml_reader)?;
+    let text = extract_next_text(&mut xml_reader)?;
+    Some(WikiPage { title, text })
+}
+
+fn extract_next_text<R: Read>(reader: &mut EventReader<R>) -> Option<String> {
+    // Read all events until we get the text of the article:
+    while let Ok(event) = reader.next() {
+        // If the event is a start element:
+        if let XmlEvent::StartElement{name, ..} = event {
+            // And it is for text:
+            if name.local_name == "text" {
+                return match reader.next() {
+                    // Read an extra tag; it is the content of the article:
+                    Ok(XmlEvent::Characters(text)) => Some(text),
+                    // An empty <text /> tag is valid; the article simply has no content.
+                    Ok(XmlEvent::EndElement{..}) => Some(String::new()),
+                    _ => None,
+                };
+            }
+        }
+    }
+    // Reached end of file (or got an error) while looking for <text>, return nothing:
+    return None;
+}
+
+/// Reads a redirects table previously written by `generate_index` and returns a map of
+/// redirect titles to the title of the article they point to.
+pub fn read_redirects<R: Read>(redirects_in: R) -> HashMap<String, String> {
+    let mut redirects = HashMap::new();
+    for line in BufReader::new(redirects_in).lines() {
+        let line = line.expect("Unable to read redirects file. Aborting.");
+        let mut parts = line.splitn(2, '\t');
+        if let (Some(source), Some(target)) = (parts.next(), parts.next()) {
+            redirects.insert(source.to_owned(), target.to_owned());
+        }
+    }
+    redirects
+}
+
+/// Reads a backlinks table previously written by `generate_backlinks` and returns a map of
+/// article titles to the titles of the articles linking to them.
+pub fn read_backlinks<R: Read>(backlinks_in: R) -> HashMap<String, Vec<String>> {
+    let mut backlinks: HashMap<String, Vec<String>> = HashMap::new();
+    for line in BufReader::new(backlinks_in).lines() {
+        let line = line.expect("Unable to read backlinks file. Aborting.");
+        let mut parts = line.splitn(2, '\t');
+        if let (Some(target), Some(source)) = (parts.next(), parts.next()) {
+            backlinks
+                .entry(target.to_owned())
+                .or_insert_with(Vec::new)
+                .push(source.to_owned());
+        }
+    }
+    backlinks
+}
+
 #[cfg(test)]
 mod tests;
@@ -1,11 +1,81 @@
 use super::*;
+use std::io::Cursor;
+
+static DUMP: &'static str = "<mediawiki>\n  \
+    <page>\n    <title>Montreal</title>\n    <ns>0</ns>\n    <id>1</id>\n    <revision>\n      \
+    <text xml:space=\"preserve\">'''Montreal''' is a city in [[Quebec]].\n\nIt is in [[Canada]], like [[Quebec]].</text>\n    \
+    </revision>\n  </page>\n  \
+    <page>\n    <title>Montréal</title>\n    <ns>0</ns>\n    <id>2</id>\n    \
+    <redirect title=\"Montreal\" />\n    <revision>\n      \
+    <text xml:space=\"preserve\">#REDIRECT [[Montreal]]</text>\n    \
+    </revision>\n  </page>\n  \
+    <page>\n    <title>Template:Infobox</title>\n    <ns>10</ns>\n    <id>3</id>\n    <revision>\n      \
+    <text xml:space=\"preserve\">{{{name}}}</text>\n    \
+    </revision>\n  </page>\n\
+    </mediawiki>";
 
 #[test]
 fn generate_index_for_nothing_writes_nothing() {
-    let mut inp = String::from("");
-    let mut out = Vec::<u8>::new();
+    let inp = Cursor::new(String::from("").into_bytes());
+    let mut out = Cursor::new(Vec::<u8>::new());
+    let mut redirects = Vec::<u8>::new();
     // Generate the index for empty content, make sure it returns `Ok`
-    assert!(generate_index(&mut inp.as_bytes(), &mut out).is_ok());
+    assert!(generate_index(inp, &mut out, &mut redirects).is_ok());
     // MAke sure the output did not get written onto:
-    assert!(out.is_empty());
+    assert!(out.get_ref().is_empty());
+    assert!(redirects.is_empty());
+}
+
+#[test]
+fn generate_index_writes_articles_and_redirects() {
+    let mut out = Cursor::new(Vec::<u8>::new());
+    let mut redirects = Vec::<u8>::new();
+    assert!(generate_index(Cursor::new(DUMP.as_bytes()), &mut out, &mut redirects).is_ok());
+    assert_eq!(
+        String::from_utf8(out.into_inner()).unwrap(),
+        format!("{},Montreal\r\n", DUMP.find("<page>").unwrap())
+    );
+    assert_eq!(
+        String::from_utf8(redirects).unwrap(),
+        "Montréal\tMontreal\r\n"
+    );
+}
+
+#[test]
+fn read_page_at_indexed_position() {
+    let mut out = Cursor::new(Vec::<u8>::new());
+    generate_index(Cursor::new(DUMP.as_bytes()), &mut out, Vec::<u8>::new()).unwrap();
+    let index = read_index(Cursor::new(out.into_inner()));
+    let page = read_page_at(Cursor::new(DUMP.as_bytes()), index["Montreal"]).unwrap();
+    assert_eq!(page.title, "Montreal");
+    assert_eq!(
+        page.text,
+        "'''Montreal''' is a city in [[Quebec]].\n\nIt is in [[Canada]], like [[Quebec]]."
+    );
+}
+
+#[test]
+fn read_redirects_splits_on_tabs() {
+    let redirects = read_redirects("Washington, D.C.\tWashington D.C.\r\nNYC\tNew York City\r\n".as_bytes());
+    assert_eq!(redirects.len(), 2);
+    assert_eq!(redirects["Washington, D.C."], "Washington D.C.");
+    assert_eq!(redirects["NYC"], "New York City");
+}
+
+#[test]
+fn generate_backlinks_writes_each_link_once() {
+    let mut out = Vec::<u8>::new();
+    assert!(generate_backlinks(Cursor::new(DUMP.as_bytes()), &mut out).is_ok());
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "Canada\tMontreal\r\nQuebec\tMontreal\r\n"
+    );
+}
+
+#[test]
+fn read_backlinks_groups_sources_by_target() {
+    let backlinks = read_backlinks("Quebec\tMontreal\r\nCanada\tMontreal\r\nQuebec\tQuebec City\r\n".as_bytes());
+    assert_eq!(backlinks.len(), 2);
+    assert_eq!(backlinks["Quebec"], vec!["Montreal", "Quebec City"]);
+    assert_eq!(backlinks["Canada"], vec!["Montreal"]);
 }